@@ -1,8 +1,13 @@
 use std::path::Path;
 
 use anyhow::Result;
+use semver::Version;
 use serde::Deserialize;
 
+use crate::driver_db::Driver;
+use crate::website_db::Interface;
+use crate::{Listing, Provenance};
+
 pub fn from_csv(path: impl AsRef<Path>) -> Result<List> {
     let mut rdr = csv::Reader::from_path(path)?;
     Ok(List(rdr.deserialize::<Entry>().collect::<Result<_, _>>()?))
@@ -18,3 +23,35 @@ pub struct Entry {
     pub description: String,
     pub links: String,
 }
+
+impl Entry {
+    /// Interfaces in the interface column, like `I2C / SPI` or `SPI+GPIO`, ignoring unknown ones
+    pub fn interfaces(&self) -> Vec<Interface> {
+        let mut interfaces: Vec<Interface> = self
+            .interface
+            .split(['/', '+', ','])
+            .map(str::trim)
+            .filter_map(|interface| interface.parse().ok())
+            .collect();
+        interfaces.sort();
+        interfaces.dedup();
+        interfaces
+    }
+
+    /// Listing of the driver, without any chip information
+    ///
+    /// The list doesn't describe the interfaces, so even I2C and SPI are only given by name.
+    pub fn listing(&self) -> Listing {
+        Listing {
+            driver: Driver {
+                manifest_version: Version::new(0, 0, 0),
+                meta: Default::default(),
+                dev_boards: Default::default(),
+                interfaces: Default::default(),
+                resources: vec![],
+            },
+            provenance: Provenance::Legacy,
+            other_interfaces: self.interfaces(),
+        }
+    }
+}
@@ -1,22 +1,118 @@
-use anyhow::bail;
-use drivers::driver_db::{Driver, I2c, Interfaces, Spi, SpiDeviceType};
+use anyhow::{bail, Context};
+use clap::{Parser, Subcommand};
+use drivers::build_report::{BuildReport, Diagnostic, DiagnosticKind};
+use drivers::diff::{DbDiff, DbSnapshot};
+use drivers::driver_db::boards::{Board, BoardRegistry};
+use drivers::driver_db::manifest::{self, Manifest};
+use drivers::driver_db::Driver;
+use drivers::kicad::{self, KicadLibrary};
+use drivers::sqlite;
+use drivers::typescript;
 use drivers::website_db::indexes::Indexes;
-use drivers::{dumpsterbase, FullCrate};
-use schemars::{schema_for, JsonSchema};
-use semver::Version;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use drivers::website_db::search::SearchIndex;
+use drivers::website_db::{feed, split};
+use drivers::{dumpsterbase, FullCrate, FullCrateDb, Listing, Provenance};
+use schemars::generate::SchemaSettings;
+use schemars::schema_for;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 use std::{ffi::OsStr, path::Path};
+use url::Url;
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
-#[serde(deny_unknown_fields)]
-struct FullCrateDb {
-    crates: Vec<FullCrate>,
-    indexes: Indexes,
-    created_at: chrono::DateTime<chrono::Utc>,
+#[derive(Debug, Parser)]
+struct Args {
+    /// Fail if any errors were reported, like drivers that could not be included in the database
+    /// or unresolved dependencies
+    #[arg(long)]
+    strict: bool,
+    /// Fail on warnings as well, like legacy entries without a crate, together with `--strict`
+    #[arg(long, requires = "strict")]
+    deny_warnings: bool,
+    /// Write the build report as JSON to this file
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// Write the build report in human-readable form to this file
+    #[arg(long)]
+    summary: Option<PathBuf>,
+    /// Check KiCad symbols against a checkout of the KiCad symbol libraries in this directory
+    #[arg(long)]
+    kicad_symbols: Option<PathBuf>,
+    /// Check footprints of KiCad symbols against a checkout of the KiCad footprint libraries in this directory
+    #[arg(long, requires = "kicad_symbols")]
+    kicad_footprints: Option<PathBuf>,
+    /// Write a CSV table mapping KiCad symbols to the Rust drivers supporting them to this file
+    #[arg(long)]
+    kicad_fields: Option<PathBuf>,
+    /// Also write the crate database as SQLite to this file
+    #[arg(long)]
+    sqlite: Option<PathBuf>,
+    /// Also write the crate database split into content-hashed files for the website to this directory
+    #[arg(long)]
+    split: Option<PathBuf>,
+    /// Also write Atom and JSON feeds of new and updated drivers to this directory
+    #[arg(long, requires = "site_url")]
+    feeds: Option<PathBuf>,
+    /// Public URL of the website, with the feeds published in its `feeds/` directory
+    #[arg(long)]
+    site_url: Option<Url>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Rewrite driver descriptions in older manifest versions to the current one
+    Migrate {
+        /// Only list outdated driver descriptions and fail if there are any
+        #[arg(long)]
+        check: bool,
+        /// Driver descriptions to migrate, all of `driver-db` by default
+        files: Vec<PathBuf>,
+    },
+    /// Generate the TypeScript definitions of the crate database for the frontend
+    Typescript {
+        /// Only check that the definitions are up to date and fail if they are not
+        #[arg(long)]
+        check: bool,
+        /// File to write the definitions to
+        #[arg(long, default_value = "frontend/src/crate-db.ts")]
+        output: PathBuf,
+    },
+    /// Compare two crate databases and describe what changed between them
+    Diff {
+        /// Crate database of the previous build
+        old: PathBuf,
+        /// Crate database of the current build
+        new: PathBuf,
+        /// Write the changes as JSON to this file
+        #[arg(long)]
+        json: Option<PathBuf>,
+        /// Write the changes as a Markdown changelog to this file instead of printing them
+        #[arg(long)]
+        markdown: Option<PathBuf>,
+        /// Fail if any driver was removed
+        #[arg(long)]
+        strict: bool,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    match args.command {
+        Some(Command::Migrate { check, files }) => return migrate(files, check),
+        Some(Command::Typescript { check, output }) => return typescript(&output, check),
+        Some(Command::Diff {
+            old,
+            new,
+            json,
+            markdown,
+            strict,
+        }) => return diff(&old, &new, json, markdown, strict),
+        None => {}
+    }
+
+    let mut report = BuildReport::default();
+
     // Write out schema for easier crate description
     let schema = schema_for!(Driver);
     std::fs::write(
@@ -24,41 +120,56 @@ fn main() -> anyhow::Result<()> {
         serde_json::to_string_pretty(&schema)?,
     )?;
 
-    // Read old awesome embedded Rust list
+    std::fs::write(
+        "board-schema.json",
+        serde_json::to_string_pretty(&schema_for!(Board))?,
+    )?;
+
+    // Read the legacy awesome embedded Rust list
     let list = drivers::awesome_embedded_rust::from_csv("aer.csv")?;
-    let old_drivers: HashMap<String, Driver> = list
+    // Sorted, so unchanged crates keep their position and the split files their hashes
+    let legacy: BTreeMap<String, Listing> = list
         .0
-        .into_iter()
-        .map(|e| {
-            (
-                e.name.to_lowercase(),
-                Driver {
-                    manifest_version: Version::new(0, 0, 0),
-                    meta: Default::default(),
-                    dev_boards: Default::default(),
-                    interfaces: Interfaces {
-                        i2c: e.interface.contains("I2C").then_some(I2c {
-                            addrs: vec![],
-                            interrupt: false,
-                        }),
-                        spi: e.interface.contains("SPI").then_some(Spi {
-                            bus_type: SpiDeviceType::SpiBus,
-                            interrupt: false,
-                        }),
-                    },
-                    resources: vec![],
-                },
-            )
-        })
+        .iter()
+        .map(|entry| (entry.name.to_lowercase(), entry.listing()))
         .collect();
 
+    // Read KiCad libraries
+    let kicad = match &args.kicad_symbols {
+        Some(dir) => {
+            let mut library = KicadLibrary::from_symbol_dir(dir)?;
+            println!("Found {} KiCad symbols", library.symbol_count());
+            if let Some(dir) = &args.kicad_footprints {
+                library.read_footprint_dir(dir)?;
+                println!(
+                    "Found {} KiCad footprints",
+                    library.footprint_count().unwrap_or_default()
+                );
+            }
+            Some(library)
+        }
+        None => None,
+    };
+
+    // Read the board registry
+    let (boards, errors) = BoardRegistry::read_dir("boards".as_ref())?;
+    println!("Found {} boards", boards.boards.len());
+    for (path, e) in errors {
+        report.push(Diagnostic::new(DiagnosticKind::UnparsableToml, path, e));
+    }
+
     // Read all drivers we have listed
-    let new_drivers = read_all("driver-db".as_ref())?;
+    let new_drivers = read_all("driver-db".as_ref(), &boards, &mut report)?;
     println!("Found {} drivers", new_drivers.len());
 
-    let mut drivers = HashMap::new();
-    drivers.extend(old_drivers);
-    drivers.extend(new_drivers);
+    // Curated drivers replace their legacy entries
+    let mut drivers = legacy;
+    drivers.extend(
+        new_drivers
+            .into_iter()
+            .map(|(name, driver)| (name, Listing::from(driver))),
+    );
+    report.drivers = drivers.len();
 
     // Fetch info from crates.io
     let crates = drivers.keys().cloned().collect();
@@ -71,28 +182,63 @@ fn main() -> anyhow::Result<()> {
 
     // Write out the crate db
     let mut output = Vec::with_capacity(drivers.len());
-    for (name, driver) in drivers.into_iter() {
+    for (name, listing) in drivers.into_iter() {
         let krate = match crates.remove(&name) {
             Some(krate) => krate,
             None => {
-                eprintln!("No crate found for driver: {name}");
+                let kind = match listing.provenance {
+                    Provenance::Curated => DiagnosticKind::UnknownCrate,
+                    Provenance::Legacy => DiagnosticKind::UnknownLegacyCrate,
+                };
+                report.push(Diagnostic::new(kind, name, "No crate found for driver"));
                 continue;
             }
         };
-        let full = match FullCrate::new(driver, krate) {
+        let mut full = match FullCrate::new(listing, krate) {
             Ok(full) => full,
-            Err(e) => {
-                eprintln!("Error creating full crate: {e}");
+            Err(diagnostic) => {
+                report.push(diagnostic);
                 continue;
             }
         };
 
+        for dep in full.unresolved_dependencies() {
+            report.push(Diagnostic::new(
+                DiagnosticKind::UnresolvedDependency,
+                &full.name,
+                format!("No version of {} matches requirement {}", dep.name, dep.req),
+            ));
+        }
+
+        for diagnostic in full.link_boards(&boards) {
+            report.push(diagnostic);
+        }
+
+        if let Some(library) = &kicad {
+            for diagnostic in full.check_kicad(library) {
+                report.push(diagnostic);
+            }
+        }
+
         output.push(full);
     }
+    report.crates = output.len();
+
+    if let Some(path) = &args.kicad_fields {
+        kicad::write_driver_fields(&output, path)?;
+    }
 
     let indexes = Indexes::from(output.as_slice());
+    if let Some(path) = &args.sqlite {
+        sqlite::write_sqlite(&output, &indexes, &boards, path)?;
+    }
+
+    let search = SearchIndex::from(output.as_slice());
     let full_output = FullCrateDb {
         crates: output,
+        categories: indexes.category_tree(),
+        search,
+        boards: boards.boards.into_values().collect(),
         indexes,
         created_at: chrono::Utc::now(),
     };
@@ -102,31 +248,89 @@ fn main() -> anyhow::Result<()> {
         serde_json::to_string_pretty(&full_output)?,
     )?;
 
-    // Write out schema
+    if let Some(dir) = &args.split {
+        let manifest = split::write_split(
+            dir,
+            &full_output.crates,
+            &full_output.indexes,
+            &full_output.search,
+            &full_output.categories,
+            &full_output.boards,
+            full_output.created_at,
+        )?;
+        println!(
+            "Wrote {} crates and {} indexes to {}",
+            manifest.crates.len(),
+            manifest.indexes.len(),
+            dir.display()
+        );
+    }
+
+    if let (Some(dir), Some(site)) = (&args.feeds, &args.site_url) {
+        let written = feed::write_feeds(dir, &full_output.crates, site)?;
+        println!("Wrote {written} feeds to {}", dir.display());
+    }
+
+    // Write out schema, of the database as it is written
+    let schema = SchemaSettings::default()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<FullCrateDb>();
     std::fs::write(
         "full-crate-db-schema.json",
-        serde_json::to_string_pretty(&schema_for!(FullCrateDb))?,
+        serde_json::to_string_pretty(&schema)?,
     )?;
 
+    // Write out the build report
+    report
+        .diagnostics
+        .sort_by(|a, b| (a.kind, &a.subject).cmp(&(b.kind, &b.subject)));
+    print!("{report}");
+    if let Some(path) = &args.report {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    }
+    if let Some(path) = &args.summary {
+        std::fs::write(path, report.to_string())?;
+    }
+
+    if args.strict && report.errors() > 0 {
+        bail!("{} errors were reported in strict mode", report.errors());
+    }
+    if args.deny_warnings && report.warnings() > 0 {
+        bail!(
+            "{} warnings were reported with --deny-warnings",
+            report.warnings()
+        );
+    }
+
     Ok(())
 }
 
-fn read_all(dir: &Path) -> anyhow::Result<HashMap<String, Driver>> {
+fn read_all(
+    dir: &Path,
+    boards: &BoardRegistry,
+    report: &mut BuildReport,
+) -> anyhow::Result<HashMap<String, Driver>> {
     let mut drivers = HashMap::new();
     for info in std::fs::read_dir(dir)? {
-        match parse_crate(info?.path().as_path()) {
-            Ok((name, driver)) => {
-                drivers.insert(name, driver);
+        let path = info?.path();
+        match parse_crate(&path, boards) {
+            Ok((name, manifest)) => {
+                drivers.insert(name, manifest.driver);
             }
             Err(e) => {
-                eprintln!("Error parsing driver: {e}");
+                report.push(Diagnostic::new(
+                    DiagnosticKind::UnparsableToml,
+                    path.display().to_string(),
+                    e,
+                ));
             }
         }
     }
     Ok(drivers)
 }
 
-pub fn parse_crate(path: &Path) -> anyhow::Result<(String, Driver)> {
+pub fn parse_crate(path: &Path, boards: &BoardRegistry) -> anyhow::Result<(String, Manifest)> {
     if path.extension().is_none_or(|ext| ext != OsStr::new("toml")) {
         bail!("Driver info has wrong extension: {path:?}");
     }
@@ -135,7 +339,107 @@ pub fn parse_crate(path: &Path) -> anyhow::Result<(String, Driver)> {
         bail!("Driver info has no file stem: {path:?}");
     };
 
-    let driver_info = toml::from_str(&std::fs::read_to_string(path)?)?;
+    let manifest = Manifest::parse(&std::fs::read_to_string(path)?, boards)?;
+    manifest.driver.validate()?;
+
+    Ok((crate_name.to_string_lossy().into_owned(), manifest))
+}
+
+fn migrate(mut files: Vec<PathBuf>, check: bool) -> anyhow::Result<()> {
+    let (boards, errors) = BoardRegistry::read_dir("boards".as_ref())?;
+    if let Some((path, e)) = errors.into_iter().next() {
+        bail!("Could not read board {path}: {e}");
+    }
+
+    if files.is_empty() {
+        for entry in std::fs::read_dir("driver-db")? {
+            files.push(entry?.path());
+        }
+        files.sort();
+    }
+
+    let mut outdated = 0;
+    for path in files {
+        let source = std::fs::read_to_string(&path)?;
+        let (_, manifest) =
+            parse_crate(&path, &boards).with_context(|| format!("Could not read {path:?}"))?;
+        let Some(migrated) = manifest::migrate_source(&source, &boards)
+            .with_context(|| format!("Could not migrate {path:?}"))?
+        else {
+            continue;
+        };
+
+        outdated += 1;
+        if check {
+            println!(
+                "{} uses manifest version {}",
+                path.display(),
+                manifest.version
+            );
+        } else {
+            std::fs::write(&path, migrated)?;
+            println!(
+                "Migrated {} from manifest version {} to {}",
+                path.display(),
+                manifest.version,
+                manifest::CURRENT_VERSION
+            );
+        }
+    }
+
+    if check && outdated > 0 {
+        bail!("{outdated} driver descriptions need to be migrated");
+    }
 
-    Ok((crate_name.to_string_lossy().into_owned(), driver_info))
+    Ok(())
+}
+
+/// Write the TypeScript definitions of the crate database to `output`, or check they are up to date
+fn typescript(output: &Path, check: bool) -> anyhow::Result<()> {
+    let generated = typescript::crate_db()?;
+
+    if check {
+        let current = std::fs::read_to_string(output)
+            .with_context(|| format!("Could not read {}", output.display()))?;
+        if current != generated {
+            bail!(
+                "{} is out of date, run `cargo run --bin read-driver-db -- typescript` to update it",
+                output.display()
+            );
+        }
+        println!("{} is up to date", output.display());
+    } else {
+        std::fs::write(output, generated)?;
+        println!("Wrote {}", output.display());
+    }
+
+    Ok(())
+}
+
+/// Describe the changes from the crate database in `old` to the one in `new`
+fn diff(
+    old: &Path,
+    new: &Path,
+    json: Option<PathBuf>,
+    markdown: Option<PathBuf>,
+    strict: bool,
+) -> anyhow::Result<()> {
+    let new = DbSnapshot::read(new).with_context(|| format!("Could not read {}", new.display()))?;
+    // Only the compared fields are read, so databases of older builds can still be compared
+    let old = DbSnapshot::read(old).with_context(|| format!("Could not read {}", old.display()))?;
+    let diff = DbDiff::new(&old, &new);
+
+    if let Some(path) = &json {
+        std::fs::write(path, serde_json::to_string_pretty(&diff)?)?;
+    }
+    match &markdown {
+        Some(path) => std::fs::write(path, diff.to_string())?,
+        None => print!("{diff}"),
+    }
+
+    if strict && !diff.removed.is_empty() {
+        bail!("{} drivers were removed", diff.removed.len());
+    }
+
+    Ok(())
 }
@@ -0,0 +1,262 @@
+//! Read-only HTTP/JSON API over a crate database written by `read-driver-db`
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use clap::Parser;
+use drivers::i2c;
+use drivers::website_db::split::CrateSummary;
+use drivers::FullCrateDb;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+use url::Url;
+
+#[derive(Debug, Parser)]
+struct Args {
+    /// Crate database to serve
+    #[arg(long, default_value = "full-crate-db.json")]
+    db: PathBuf,
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: String,
+}
+
+/// Response of a request, with its status code
+type Reply = (u16, Value);
+
+/// Crate in a list of results
+#[derive(Debug, Serialize)]
+struct Listed {
+    /// Position in the crate database, as used by the indexes
+    id: usize,
+    /// Relevance for the search query, if there was one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score: Option<u32>,
+    #[serde(flatten)]
+    summary: CrateSummary,
+}
+
+struct Api {
+    db: FullCrateDb,
+    /// Crates in each entry of each index, keys of plain sets are `true`
+    indexes: BTreeMap<String, BTreeMap<String, Vec<usize>>>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let db = FullCrateDb::read(&args.db)?;
+    let api = Api::new(db)?;
+    println!(
+        "Serving {} crates from {} on http://{}",
+        api.db.crates.len(),
+        args.db.display(),
+        args.listen
+    );
+
+    let server = Server::http(&args.listen).map_err(|e| anyhow::anyhow!(e))?;
+    for request in server.incoming_requests() {
+        let (status, body) = match request.method() {
+            Method::Get => match Url::parse(&format!("http://localhost{}", request.url())) {
+                Ok(url) => api.handle(&url),
+                Err(e) => error(400, e),
+            },
+            _ => error(405, "Only GET requests are supported"),
+        };
+        respond(request, status, &body);
+    }
+
+    Ok(())
+}
+
+fn respond(request: Request, status: u16, body: &Value) {
+    let header = Header::from_bytes("Content-Type", "application/json")
+        .expect("Content-Type header is valid");
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header);
+    if let Err(e) = request.respond(response) {
+        eprintln!("Could not send response: {e}");
+    }
+}
+
+fn error(status: u16, message: impl std::fmt::Display) -> Reply {
+    (status, json!({ "error": message.to_string() }))
+}
+
+fn ok(value: impl Serialize) -> Reply {
+    match serde_json::to_value(value) {
+        Ok(value) => (200, value),
+        Err(e) => error(500, e),
+    }
+}
+
+impl Api {
+    fn new(db: FullCrateDb) -> anyhow::Result<Self> {
+        let Value::Object(fields) = serde_json::to_value(&db.indexes)? else {
+            anyhow::bail!("Indexes are not serialized as an object");
+        };
+
+        let mut indexes = BTreeMap::new();
+        for (name, index) in fields {
+            let entries = match index {
+                Value::Object(keys) => keys
+                    .into_iter()
+                    .map(|(key, crates)| Ok((key, serde_json::from_value(crates)?)))
+                    .collect::<anyhow::Result<_>>()?,
+                crates => BTreeMap::from([("true".to_owned(), serde_json::from_value(crates)?)]),
+            };
+            indexes.insert(name, entries);
+        }
+
+        Ok(Self { db, indexes })
+    }
+
+    fn handle(&self, url: &Url) -> Reply {
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        let segments: Vec<&str> = url
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+
+        match segments.as_slice() {
+            [] => ok(json!({
+                "crates": "/crates?<index>=<key>&q=<search>",
+                "crate": "/crates/<name>",
+                "search": "/search?q=<query>",
+                "indexes": "/indexes",
+                "index": "/indexes/<name>",
+                "categories": "/categories",
+                "boards": "/boards",
+                "board": "/boards/<id>",
+                "i2c_address": "/i2c/<address>",
+                "i2c_plan": "/i2c/plan?crates=<name>,<name>",
+                "created_at": self.db.created_at,
+            })),
+            ["crates"] => self.list(&query),
+            ["crates", name] => match self.db.get(name) {
+                Some((_, krate)) => ok(krate),
+                None => error(404, format!("No crate called {name}")),
+            },
+            ["search"] => match query.iter().find(|(key, _)| key == "q") {
+                Some((_, q)) => ok(self.search(q)),
+                None => error(400, "Missing search query q"),
+            },
+            ["indexes"] => ok(self
+                .indexes
+                .iter()
+                .map(|(name, entries)| (name, entries.keys().collect::<Vec<_>>()))
+                .collect::<BTreeMap<_, _>>()),
+            ["indexes", name] => match self.indexes.get(*name) {
+                Some(index) => ok(index),
+                None => error(404, format!("No index called {name}")),
+            },
+            ["categories"] => ok(&self.db.categories),
+            ["boards"] => ok(&self.db.boards),
+            ["boards", id] => match self.db.boards.iter().find(|board| board.id == *id) {
+                Some(board) => ok(board),
+                None => error(404, format!("No board called {id}")),
+            },
+            ["i2c", "plan"] => self.plan_bus(&query),
+            ["i2c", address] => match parse_address(address) {
+                Some(address) => ok(self.at_address(address)),
+                None => error(400, format!("Invalid I2C address {address}")),
+            },
+            _ => error(404, format!("Unknown endpoint {}", url.path())),
+        }
+    }
+
+    fn listed(&self, id: usize, score: Option<u32>) -> Listed {
+        Listed {
+            id,
+            score,
+            summary: CrateSummary::from(&self.db.crates[id]),
+        }
+    }
+
+    /// Crates in every given index entry, ranked by the search query `q` if there is one
+    fn list(&self, query: &[(String, String)]) -> Reply {
+        let mut selected: Vec<usize> = (0..self.db.crates.len()).collect();
+        let mut search = None;
+
+        for (name, key) in query {
+            if name == "q" {
+                search = Some(key);
+                continue;
+            }
+
+            let Some(index) = self.indexes.get(name) else {
+                return error(400, format!("No index called {name}"));
+            };
+            let crates = index.get(key).map(Vec::as_slice).unwrap_or_default();
+            selected.retain(|id| crates.contains(id));
+        }
+
+        match search {
+            Some(q) => ok(self
+                .db
+                .search
+                .search(q)
+                .into_iter()
+                .filter(|(id, _)| selected.contains(id))
+                .map(|(id, score)| self.listed(id, Some(score)))
+                .collect::<Vec<_>>()),
+            None => ok(selected
+                .into_iter()
+                .map(|id| self.listed(id, None))
+                .collect::<Vec<_>>()),
+        }
+    }
+
+    fn search(&self, q: &str) -> Vec<Listed> {
+        self.db
+            .search
+            .search(q)
+            .into_iter()
+            .map(|(id, score)| self.listed(id, Some(score)))
+            .collect()
+    }
+
+    /// Crates for chips that can use `address`
+    fn at_address(&self, address: u8) -> Vec<Listed> {
+        self.db
+            .crates
+            .iter()
+            .enumerate()
+            .filter(|(_, krate)| {
+                krate
+                    .interfaces
+                    .i2c
+                    .as_ref()
+                    .is_some_and(|i2c| i2c.addrs.contains(&address))
+            })
+            .map(|(id, _)| self.listed(id, None))
+            .collect()
+    }
+
+    fn plan_bus(&self, query: &[(String, String)]) -> Reply {
+        let Some((_, names)) = query.iter().find(|(key, _)| key == "crates") else {
+            return error(400, "Missing comma-separated list of crates");
+        };
+
+        let mut devices = vec![];
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match self.db.get(name) {
+                Some((_, krate)) => devices.push(krate),
+                None => return error(404, format!("No crate called {name}")),
+            }
+        }
+
+        ok(i2c::plan_bus(&devices))
+    }
+}
+
+/// Parse an address like `0x40` or `64`
+fn parse_address(address: &str) -> Option<u8> {
+    let address = match address.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16).ok()?,
+        None => address.parse().ok()?,
+    };
+    (address < 0x80).then_some(address)
+}
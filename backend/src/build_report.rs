@@ -0,0 +1,147 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum DiagnosticKind {
+    /// A driver description could not be read or parsed
+    UnparsableToml,
+    /// No crate with the name of a driver was found in the crates.io dump
+    UnknownCrate,
+    /// No crate with the name of an entry in the legacy list was found, not all of them are published
+    UnknownLegacyCrate,
+    /// The crates.io dump contains no versions for a crate
+    NoVersions,
+    /// No published version satisfies a dependency requirement
+    UnresolvedDependency,
+    /// A KiCad symbol of a driver does not exist in the KiCad libraries
+    UnknownKicadSymbol,
+    /// The default footprint of a KiCad symbol does not exist in the KiCad libraries
+    UnknownKicadFootprint,
+    /// A driver without KiCad symbols has part numbers matching symbols in the KiCad libraries
+    SuggestedKicadSymbol,
+    /// A driver references a development board that is not in the board registry
+    UnknownBoard,
+}
+
+impl DiagnosticKind {
+    /// Whether a diagnostic of this kind fails a `--strict` build
+    ///
+    /// Legacy entries without a crate and suggested KiCad symbols are warnings, they only fail a
+    /// build that denies warnings.
+    pub fn is_error(&self) -> bool {
+        !matches!(
+            self,
+            DiagnosticKind::UnknownLegacyCrate | DiagnosticKind::SuggestedKicadSymbol
+        )
+    }
+}
+
+impl Display for DiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DiagnosticKind::UnparsableToml => "unparsable TOML",
+            DiagnosticKind::UnknownCrate => "unknown crate",
+            DiagnosticKind::UnknownLegacyCrate => "unknown legacy crate",
+            DiagnosticKind::NoVersions => "no versions",
+            DiagnosticKind::UnresolvedDependency => "unresolved dependency requirement",
+            DiagnosticKind::UnknownKicadSymbol => "unknown KiCad symbol",
+            DiagnosticKind::UnknownKicadFootprint => "unknown KiCad footprint",
+            DiagnosticKind::SuggestedKicadSymbol => "suggested KiCad symbol",
+            DiagnosticKind::UnknownBoard => "unknown board",
+        })
+    }
+}
+
+/// Something that went wrong while building the crate database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// Driver file or crate this diagnostic is about
+    pub subject: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(kind: DiagnosticKind, subject: impl Into<String>, message: impl Display) -> Self {
+        Self {
+            kind,
+            subject: subject.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}: {}", self.kind, self.subject, self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Summary of a crate database build
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct BuildReport {
+    /// Number of drivers that were found
+    pub drivers: usize,
+    /// Number of crates written to the database
+    pub crates: usize,
+    pub counts: BTreeMap<DiagnosticKind, usize>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl BuildReport {
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        *self.counts.entry(diagnostic.kind).or_default() += 1;
+        self.diagnostics.push(diagnostic);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Number of diagnostics that fail a `--strict` build
+    pub fn errors(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.kind.is_error())
+            .count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.diagnostics.len() - self.errors()
+    }
+}
+
+impl Display for BuildReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Wrote {} of {} drivers, {} errors, {} warnings",
+            self.crates,
+            self.drivers,
+            self.errors(),
+            self.warnings(),
+        )?;
+
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f)?;
+        for (kind, count) in &self.counts {
+            writeln!(f, "- {kind}: {count}")?;
+        }
+
+        writeln!(f)?;
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "- {diagnostic}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,413 @@
+//! Changes between two builds of the crate database, for changelogs and catching regressions
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use crate::dumpsterbase::DependencyKind;
+use crate::ShortDependency;
+
+/// Number of crates listed in the Markdown table of download changes
+const TOP_DOWNLOADS: usize = 10;
+
+/// Parts of a crate database that are compared
+///
+/// This is read leniently, other fields are ignored and missing ones are left empty, so databases
+/// written with an older or newer schema can still be compared.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DbSnapshot {
+    pub crates: Vec<CrateSnapshot>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DbSnapshot {
+    /// Read the parts of a crate database written by `read-driver-db` that are compared
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        Ok(serde_json::from_reader(file)?)
+    }
+}
+
+/// Compared fields of a [`FullCrate`](crate::FullCrate)
+#[derive(Debug, Clone, Deserialize)]
+pub struct CrateSnapshot {
+    pub name: String,
+    pub version: Version,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub downloads: u64,
+    #[serde(default)]
+    pub msrv: Option<MsrvSnapshot>,
+    #[serde(default)]
+    pub license: String,
+    #[serde(default)]
+    pub dependencies: Vec<DependencySnapshot>,
+}
+
+/// Compared fields of a [`Msrv`](crate::msrv::Msrv)
+#[derive(Debug, Clone, Deserialize)]
+pub struct MsrvSnapshot {
+    pub version: Version,
+}
+
+/// Compared fields of a [`ShortDependency`]
+#[derive(Debug, Clone, Deserialize)]
+pub struct DependencySnapshot {
+    pub name: String,
+    #[serde(default)]
+    pub req: VersionReq,
+    #[serde(default)]
+    pub version: Option<Version>,
+    #[serde(default = "normal_kind")]
+    pub kind: DependencyKind,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+fn normal_kind() -> DependencyKind {
+    DependencyKind::Normal
+}
+
+impl From<&DependencySnapshot> for ShortDependency {
+    fn from(dep: &DependencySnapshot) -> Self {
+        Self {
+            name: dep.name.clone(),
+            req: dep.req.clone(),
+            version: dep.version.clone(),
+            unresolved: None,
+            kind: dep.kind,
+            optional: dep.optional,
+            default_features: true,
+            target: None,
+        }
+    }
+}
+
+/// Value of a field in the old and the new database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    /// The change from `old` to `new`, if there is one
+    fn of(old: T, new: T) -> Option<Self> {
+        (old != new).then_some(Self { old, new })
+    }
+}
+
+/// Crate that is only in one of the databases
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ListedCrate {
+    pub name: String,
+    pub version: Version,
+    pub description: String,
+}
+
+impl From<&CrateSnapshot> for ListedCrate {
+    fn from(krate: &CrateSnapshot) -> Self {
+        Self {
+            name: krate.name.clone(),
+            version: krate.version.clone(),
+            description: krate.description.clone(),
+        }
+    }
+}
+
+/// Changes to a crate that is in both databases
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CrateChanges {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub version: Option<Change<Version>>,
+    /// Declared or estimated minimum supported Rust version
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub msrv: Option<Change<Option<Version>>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub license: Option<Change<String>>,
+    /// Always enabled normal dependencies whose semver-compatible ranges changed, by crate name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub dependencies: BTreeMap<String, Change<BTreeSet<String>>>,
+}
+
+impl CrateChanges {
+    fn new(old: &CrateSnapshot, new: &CrateSnapshot) -> Self {
+        let old_deps = dependency_ranges(old);
+        let new_deps = dependency_ranges(new);
+        let names: BTreeSet<&String> = old_deps.keys().chain(new_deps.keys()).collect();
+        let dependencies = names
+            .into_iter()
+            .filter_map(|name| {
+                let change = Change::of(
+                    old_deps.get(name).cloned().unwrap_or_default(),
+                    new_deps.get(name).cloned().unwrap_or_default(),
+                )?;
+                Some((name.clone(), change))
+            })
+            .collect();
+
+        let msrv = |krate: &CrateSnapshot| krate.msrv.as_ref().map(|msrv| msrv.version.clone());
+
+        Self {
+            name: new.name.clone(),
+            version: Change::of(old.version.clone(), new.version.clone()),
+            msrv: Change::of(msrv(old), msrv(new)),
+            license: Change::of(old.license.clone(), new.license.clone()),
+            dependencies,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.version.is_none()
+            && self.msrv.is_none()
+            && self.license.is_none()
+            && self.dependencies.is_empty()
+    }
+}
+
+/// Semver-compatible ranges of the always enabled normal dependencies of `krate`, by crate name
+fn dependency_ranges(krate: &CrateSnapshot) -> BTreeMap<String, BTreeSet<String>> {
+    let mut ranges: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for dep in krate.dependencies.iter().map(ShortDependency::from) {
+        if dep.is_required() {
+            ranges
+                .entry(dep.name.clone())
+                .or_default()
+                .insert(dep.compatible_range());
+        }
+    }
+    ranges
+}
+
+/// All-time downloads of a crate in both databases
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DownloadChange {
+    pub name: String,
+    pub old: u64,
+    pub new: u64,
+}
+
+impl DownloadChange {
+    pub fn delta(&self) -> i64 {
+        self.new as i64 - self.old as i64
+    }
+}
+
+/// Differences between two crate databases
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DbDiff {
+    pub old_created_at: DateTime<Utc>,
+    pub new_created_at: DateTime<Utc>,
+    pub added: Vec<ListedCrate>,
+    pub removed: Vec<ListedCrate>,
+    /// Crates with a new version, MSRV, license or dependencies
+    pub changed: Vec<CrateChanges>,
+    /// Crates with more or fewer downloads, the largest increase first
+    pub downloads: Vec<DownloadChange>,
+}
+
+impl DbDiff {
+    pub fn new(old: &DbSnapshot, new: &DbSnapshot) -> Self {
+        let old_crates: BTreeMap<&str, &CrateSnapshot> =
+            old.crates.iter().map(|c| (c.name.as_str(), c)).collect();
+        let new_crates: BTreeMap<&str, &CrateSnapshot> =
+            new.crates.iter().map(|c| (c.name.as_str(), c)).collect();
+
+        let added = new_crates
+            .iter()
+            .filter(|(name, _)| !old_crates.contains_key(*name))
+            .map(|(_, krate)| ListedCrate::from(*krate))
+            .collect();
+        let removed = old_crates
+            .iter()
+            .filter(|(name, _)| !new_crates.contains_key(*name))
+            .map(|(_, krate)| ListedCrate::from(*krate))
+            .collect();
+
+        let mut changed = vec![];
+        let mut downloads = vec![];
+        for (name, new_crate) in &new_crates {
+            let Some(old_crate) = old_crates.get(name) else {
+                continue;
+            };
+
+            let changes = CrateChanges::new(old_crate, new_crate);
+            if !changes.is_empty() {
+                changed.push(changes);
+            }
+            if old_crate.downloads != new_crate.downloads {
+                downloads.push(DownloadChange {
+                    name: name.to_string(),
+                    old: old_crate.downloads,
+                    new: new_crate.downloads,
+                });
+            }
+        }
+        downloads.sort_by_key(|change| std::cmp::Reverse(change.delta()));
+
+        Self {
+            old_created_at: old.created_at,
+            new_created_at: new.created_at,
+            added,
+            removed,
+            changed,
+            downloads,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.downloads.is_empty()
+    }
+}
+
+fn crate_link(name: &str) -> String {
+    format!("[`{name}`](https://crates.io/crates/{name})")
+}
+
+fn or_none(version: &Option<Version>) -> String {
+    match version {
+        Some(version) => version.to_string(),
+        None => "unknown".into(),
+    }
+}
+
+fn ranges(ranges: &BTreeSet<String>) -> String {
+    ranges.iter().cloned().collect::<Vec<_>>().join(", ")
+}
+
+/// Markdown changelog
+impl Display for DbDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "# Driver changes from {} to {}",
+            self.old_created_at.date_naive(),
+            self.new_created_at.date_naive()
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{} new, {} removed and {} updated drivers",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )?;
+
+        if !self.added.is_empty() {
+            writeln!(f, "\n## New drivers\n")?;
+            for krate in &self.added {
+                writeln!(
+                    f,
+                    "- {} {}: {}",
+                    crate_link(&krate.name),
+                    krate.version,
+                    krate.description.trim()
+                )?;
+            }
+        }
+
+        if !self.removed.is_empty() {
+            writeln!(f, "\n## Removed drivers\n")?;
+            for krate in &self.removed {
+                writeln!(f, "- `{}` {}", krate.name, krate.version)?;
+            }
+        }
+
+        if !self.changed.is_empty() {
+            writeln!(f, "\n## Updated drivers\n")?;
+            for changes in &self.changed {
+                writeln!(f, "- {}", crate_link(&changes.name))?;
+                if let Some(Change { old, new }) = &changes.version {
+                    writeln!(f, "  - version {old} → {new}")?;
+                }
+                if let Some(Change { old, new }) = &changes.msrv {
+                    writeln!(f, "  - MSRV {} → {}", or_none(old), or_none(new))?;
+                }
+                if let Some(Change { old, new }) = &changes.license {
+                    writeln!(f, "  - license {old} → {new}")?;
+                }
+                for Change { old, new } in changes.dependencies.values() {
+                    match (old.is_empty(), new.is_empty()) {
+                        (true, _) => writeln!(f, "  - added dependency {}", ranges(new))?,
+                        (_, true) => writeln!(f, "  - removed dependency {}", ranges(old))?,
+                        _ => writeln!(f, "  - {} → {}", ranges(old), ranges(new))?,
+                    }
+                }
+            }
+        }
+
+        let gainers: Vec<_> = self
+            .downloads
+            .iter()
+            .filter(|change| change.delta() > 0)
+            .take(TOP_DOWNLOADS)
+            .collect();
+        if !gainers.is_empty() {
+            writeln!(f, "\n## Most new downloads\n")?;
+            writeln!(f, "| Crate | Downloads | New |")?;
+            writeln!(f, "|-------|----------:|----:|")?;
+            for change in gainers {
+                writeln!(
+                    f,
+                    "| {} | {} | +{} |",
+                    crate_link(&change.name),
+                    change.new,
+                    change.delta()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_other_schemas() {
+        let old: DbSnapshot = serde_json::from_value(serde_json::json!({
+            "crates": [{
+                "name": "ina219",
+                "version": "0.1.0",
+                "unknown_field": true,
+                "dependencies": [
+                    { "name": "embedded-hal", "version": "0.2.7" },
+                    { "name": "defmt", "version": "0.3.0", "kind": "Normal", "optional": true },
+                ],
+            }],
+            "indexes": {},
+            "created_at": "2024-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        let new: DbSnapshot = serde_json::from_value(serde_json::json!({
+            "crates": [{
+                "name": "ina219",
+                "version": "0.2.0",
+                "dependencies": [
+                    { "name": "embedded-hal", "req": "^1", "version": "1.0.0", "kind": "Normal", "optional": false },
+                ],
+            }],
+            "created_at": "2024-02-01T00:00:00Z",
+        }))
+        .unwrap();
+
+        let diff = DbDiff::new(&old, &new);
+        let changes = &diff.changed[0];
+        assert_eq!(
+            changes.dependencies.keys().collect::<Vec<_>>(),
+            ["embedded-hal"]
+        );
+    }
+}
@@ -1,23 +1,32 @@
-use boards::DevBoard;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
 pub mod boards;
 pub mod categories;
+pub mod connectors;
+pub mod electrical;
+pub mod manifest;
 pub mod manufacturers;
+pub mod measurements;
 pub mod packages;
+pub mod part_numbers;
+pub mod units;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Driver {
     /// Version of this driver description TOML schema
+    ///
+    /// Descriptions in older versions are migrated when read, `read-driver-db migrate` rewrites them.
     pub manifest_version: semver::Version,
     /// Metadata about the driver
     pub meta: Meta,
-    /// List of development boards that house this chip
+    /// Ids of development boards in `boards/` that house this chip
+    ///
+    /// Boards listing this driver for one of their chips don't need to be repeated here.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
-    pub dev_boards: Vec<DevBoard>,
+    pub dev_boards: Vec<String>,
     /// Interfaces used by this chip
     #[serde(skip_serializing_if = "Interfaces::is_empty", default)]
     pub interfaces: Interfaces,
@@ -40,14 +49,66 @@ pub struct Meta {
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub categories: Vec<categories::Category>,
     /// Part numbers of chips this driver supports
-    #[serde(skip_serializing_if = "Vec::is_empty", default)]
-    pub part_numbers: Vec<String>,
-    /// Names of KiCAD symbold for chips this driver supports
+    ///
+    /// Driver descriptions can give either just the part number or a table with details about the
+    /// variant. Repeated part numbers are merged.
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        default,
+        deserialize_with = "part_numbers::deserialize"
+    )]
+    #[schemars(with = "part_numbers::PartNumbers")]
+    pub part_numbers: Vec<part_numbers::PartNumber>,
+    /// KiCad symbols of chips this driver supports, as `Library:Symbol`, e.g. `Sensor_Motion:LIS3DH`
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub kicad_symbol: Vec<String>,
     /// Packages or footprints in which chips are available
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub packages: Vec<packages::Package>,
+    /// Specifications of the quantities measured by sensors this driver supports
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub measurements: Vec<measurements::Measurement>,
+    /// Supply voltage, logic levels, current consumption and operating temperature
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub electrical: Option<electrical::Electrical>,
+}
+
+impl Driver {
+    /// Check the parts of the description that can't be expressed in its types
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.meta.validate()?;
+        if let Some(i2c) = &self.interfaces.i2c {
+            for addr in &i2c.addrs {
+                check_i2c_address(*addr)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check that `addr` is a 7-bit I2C address
+pub fn check_i2c_address(addr: u8) -> anyhow::Result<()> {
+    if addr > 0x7f {
+        anyhow::bail!("I2C address {addr:#04x} is not a 7-bit address");
+    }
+    Ok(())
+}
+
+impl Meta {
+    /// Check that the details of the part numbers match the rest of the metadata
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for part in &self.part_numbers {
+            if let Some(package) = &part.package {
+                if !self.packages.contains(package) {
+                    anyhow::bail!(
+                        "Package {package} of part number {:?} is not listed in packages",
+                        part.number
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -1,27 +1,164 @@
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::Path;
+
+use anyhow::{bail, Context};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+use super::check_i2c_address;
+use super::connectors::Connection;
+
+/// Development or breakout board, as described in `boards/<id>.toml`
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
-pub struct DevBoard {
-    name: String,
-    manufacturer: BoardManufacturer,
-    link: Url,
-    connections: Vec<Connection>,
+#[serde(deny_unknown_fields)]
+pub struct Board {
+    /// Id drivers use to reference this board, the name of its file
+    // Read back from the crate database, but not allowed in board files
+    #[serde(default)]
+    #[schemars(skip_deserializing)]
+    pub id: String,
+    pub name: String,
+    /// Company selling this board
+    pub vendor: String,
+    /// Product number of the vendor
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sku: Option<String>,
+    /// Product page of the vendor
+    pub link: Url,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub price_class: Option<PriceClass>,
+    /// Connectors for attaching the board without soldering, besides plain pin headers
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub connectors: Vec<Connection>,
+    /// Chips on this board
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub chips: Vec<BoardChip>,
+    /// Solder jumpers for configuring the board
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub solder_jumpers: Vec<SolderJumper>,
 }
 
+/// Chip on a board
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
-pub enum BoardManufacturer {
-    Adafruit,
-    Sparkfun,
-    Mikroe,
-    #[serde(rename = "BBC")]
-    Bbc,
-    DFRobot,
+#[serde(deny_unknown_fields)]
+pub struct BoardChip {
+    /// Name of the chip, e.g. `LIS3DH`
+    pub name: String,
+    /// Crates in `driver-db` supporting this chip
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub drivers: Vec<String>,
+    /// I2C address the chip has out of the box
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub i2c_address: Option<u8>,
 }
 
+/// Solder jumper on a board
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
-pub enum Connection {
-    StemmaQt,
-    MikroBus,
+#[serde(deny_unknown_fields)]
+pub struct SolderJumper {
+    /// Label of the jumper on the board, e.g. `A0`
+    pub name: String,
+    /// What closing or cutting the jumper does
+    pub description: String,
+    /// I2C address of the chip when the jumper is toggled
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub i2c_address: Option<u8>,
+}
+
+/// Rough price of a board
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum PriceClass {
+    /// Less than 10 USD
+    Budget,
+    /// 10 to 25 USD
+    Low,
+    /// 25 to 50 USD
+    Medium,
+    /// More than 50 USD
+    High,
+}
+
+/// All boards in the `boards` directory, by id
+#[derive(Debug, Clone, Default)]
+pub struct BoardRegistry {
+    pub boards: BTreeMap<String, Board>,
+}
+
+impl BoardRegistry {
+    /// Read every `<id>.toml` in `dir`, returning the files that could not be read separately
+    pub fn read_dir(dir: &Path) -> anyhow::Result<(Self, Vec<(String, anyhow::Error)>)> {
+        let mut registry = Self::default();
+        let mut errors = vec![];
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            match parse_board(&path) {
+                Ok(board) => {
+                    registry.boards.insert(board.id.clone(), board);
+                }
+                Err(e) => errors.push((path.display().to_string(), e)),
+            }
+        }
+
+        Ok((registry, errors))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Board> {
+        self.boards.get(id)
+    }
+
+    /// Ids of the boards that carry a chip supported by `krate`
+    pub fn boards_for_driver<'a>(&'a self, krate: &'a str) -> impl Iterator<Item = &'a str> {
+        self.boards
+            .values()
+            .filter(move |board| {
+                board
+                    .chips
+                    .iter()
+                    .any(|chip| chip.drivers.iter().any(|driver| driver == krate))
+            })
+            .map(|board| board.id.as_str())
+    }
+}
+
+fn parse_board(path: &Path) -> anyhow::Result<Board> {
+    if path.extension().is_none_or(|ext| ext != OsStr::new("toml")) {
+        bail!("Board info has wrong extension: {path:?}");
+    }
+
+    let id = path
+        .file_stem()
+        .context("Board info has no file stem")?
+        .to_string_lossy()
+        .into_owned();
+
+    let mut board: Board = toml::from_str(&std::fs::read_to_string(path)?)?;
+    if !board.id.is_empty() {
+        bail!("Board id is given by the file name, remove the id field");
+    }
+    board.id = id;
+
+    for addr in board
+        .chips
+        .iter()
+        .filter_map(|chip| chip.i2c_address)
+        .chain(board.solder_jumpers.iter().filter_map(|j| j.i2c_address))
+    {
+        check_i2c_address(addr)?;
+    }
+
+    for jumper in &board.solder_jumpers {
+        if jumper.i2c_address.is_some() && board.chips.iter().all(|c| c.i2c_address.is_none()) {
+            bail!(
+                "Solder jumper {} changes the I2C address, but no chip has a default address",
+                jumper.name
+            );
+        }
+    }
+
+    Ok(board)
 }
@@ -1,106 +1,113 @@
-use std::fmt::Debug;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+use std::sync::LazyLock;
 
-use schemars::JsonSchema;
+use anyhow::{bail, Context};
+use schemars::{json_schema, JsonSchema};
 use serde::{Deserialize, Serialize};
 
-#[derive(
-    Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
-)]
-pub enum Category {
-    // Analog
-    /// Devices interacting with analog signals
-    Analog,
-
-    /// Analog to digital converters
-    #[serde(rename = "Analog::ADC")]
-    Adc,
-
-    /// Digital to analog converters
-    #[serde(rename = "Analog::DAC")]
-    Dac,
-
-    // Sensor
-    /// Devices measuring things about their environment
-    Sensor,
-
-    /// Sensors measuring electric power
-    #[serde(rename = "Sensor::PowerMeter")]
-    PowerMeter,
-
-    /// Sensors measuring acceleration
-    ///
-    /// These can also be used to determine where "down" is,
-    /// using the gravitational acceleration.
-    #[serde(rename = "Sensor::Accelerometer")]
-    Accelerometer,
-
-    /// Sensors measuring rotational acceleration
-    #[serde(rename = "Sensor::Gyroscope")]
-    Gyroscope,
-
-    /// Sensors measuring air CO2 concentration
-    #[serde(rename = "Sensor::CO2")]
-    CO2,
-
-    /// Sensors calculating air CO2 concentration from VOC values
-    #[serde(rename = "Sensor::eCO2")]
-    ECO2,
-
-    /// Sensors measuring general air quality by TVOC
-    #[serde(rename = "Sensor::TVOC")]
-    TVOC,
-
-    /// Sensors measuring air temperature
-    #[serde(rename = "Sensor::Temperature")]
-    Temperature,
-
-    /// Sensors measuring air humidity
-    #[serde(rename = "Sensor::Humidity")]
-    Humidity,
-
-    /// Sensors measuring magnetic fields
-    ///
-    /// These are commonly used as compasses, measuring the
-    /// magnetic field of the earth.
-    #[serde(rename = "Sensor::Magnetometer")]
-    Magnetometer,
-
-    // IO Expander
-    /// Devices that provide more input and/or output signals
-    IoExpander,
-
-    /// Devices that provide PWM input and/or output signals
-    #[serde(rename = "IoExpander::PWM")]
-    PwmExpander,
-
-    // Actor
-    /// Things that move things in the real world
-    Actor,
-
-    /// Chips for driving motors
-    #[serde(rename = "Actor::MotorController")]
-    MotorController,
-
-    // Display
-    /// Optical displays
-    Display,
-
-    /// OLED Screens
-    #[serde(rename = "Display::OLED")]
-    Oled,
-
-    // Timer
-    /// Chips measuring time
-    Timer,
-
-    #[serde(rename = "Timer::RTC")]
-    /// Clocks that keep track of wall time
-    ///
-    /// Often allow to measure time with an external battery.
-    Rtc,
+/// Taxonomy of categories, as defined in `categories.toml`
+static TAXONOMY: LazyLock<Taxonomy> = LazyLock::new(|| {
+    Taxonomy::parse(include_str!("categories.toml")).expect("Invalid category taxonomy")
+});
+
+/// Functionality provided by a driver
+///
+/// Categories form a tree, which is defined in `categories.toml`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Category(usize);
+
+/// Definition of a category in the taxonomy
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CategoryInfo {
+    /// Identifier used in driver descriptions, e.g. `Sensor::Temperature`
+    pub id: String,
+    /// Human-readable name
+    pub label: String,
+    pub description: String,
+    /// Id of the category containing this category
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Alternative ids that are accepted for this category
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+struct Taxonomy {
+    categories: Vec<CategoryInfo>,
+    parents: Vec<Option<Category>>,
+    by_id: HashMap<String, Category>,
+}
+
+impl Taxonomy {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct File {
+            category: Vec<CategoryInfo>,
+        }
+
+        let categories = toml::from_str::<File>(s)?.category;
+        let mut parents = Vec::with_capacity(categories.len());
+        let mut by_id = HashMap::new();
+
+        for (i, info) in categories.iter().enumerate() {
+            // Parents have to be listed first, which also rules out cycles
+            let parent = match &info.parent {
+                Some(parent) => Some(*by_id.get(parent).with_context(|| {
+                    format!(
+                        "Parent {parent:?} of {:?} is not defined before it",
+                        info.id
+                    )
+                })?),
+                None => None,
+            };
+            parents.push(parent);
+
+            for id in std::iter::once(&info.id).chain(&info.aliases) {
+                if by_id.insert(id.clone(), Category(i)).is_some() {
+                    bail!("Category {id:?} is defined more than once");
+                }
+            }
+        }
+
+        Ok(Self {
+            categories,
+            parents,
+            by_id,
+        })
+    }
 }
 
 impl Category {
+    /// All categories, parents before their children
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..TAXONOMY.categories.len()).map(Category)
+    }
+
+    pub fn info(&self) -> &'static CategoryInfo {
+        &TAXONOMY.categories[self.0]
+    }
+
+    pub fn id(&self) -> &'static str {
+        &self.info().id
+    }
+
+    pub fn label(&self) -> &'static str {
+        &self.info().label
+    }
+
+    pub fn description(&self) -> &'static str {
+        &self.info().description
+    }
+
+    /// Get the category directly containing this category
+    pub fn parent(&self) -> Option<Self> {
+        TAXONOMY.parents[self.0]
+    }
+
     /// Get a list of all categories that contain this category
     pub fn parents(&self) -> Vec<Self> {
         let mut parents = vec![];
@@ -114,41 +121,116 @@ impl Category {
         parents
     }
 
-    fn parent(&self) -> Option<Self> {
-        Some(match self {
-            Self::Analog => {
-                return None;
-            }
-            Self::Adc => Self::Analog,
-            Self::Dac => Self::Analog,
-            Self::Sensor => {
-                return None;
-            }
-            Self::PowerMeter => Self::Sensor,
-            Self::Accelerometer => Self::Sensor,
-            Self::Gyroscope => Self::Sensor,
-            Self::Magnetometer => Self::Sensor,
-            Category::CO2 => Self::Sensor,
-            Category::ECO2 => Self::Sensor,
-            Category::Temperature => Self::Sensor,
-            Category::Humidity => Self::Sensor,
-            Category::TVOC => Self::Sensor,
-            Self::IoExpander => {
-                return None;
-            }
-            Self::PwmExpander => Self::IoExpander,
-            Self::Actor => {
-                return None;
-            }
-            Self::MotorController => Self::Actor,
-            Self::Display => {
-                return None;
-            }
-            Self::Oled => Self::Display,
-            Self::Timer => {
-                return None;
-            }
-            Self::Rtc => Self::Timer,
-        })
+    /// Get the categories directly contained in this category
+    pub fn children(&self) -> impl Iterator<Item = Self> + '_ {
+        Self::all().filter(|child| child.parent() == Some(*self))
+    }
+}
+
+impl FromStr for Category {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match TAXONOMY.by_id.get(s) {
+            Some(category) => Ok(*category),
+            None => bail!("Unknown category: {s}"),
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl Debug for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self.id(), f)
+    }
+}
+
+impl Serialize for Category {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for Category {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl JsonSchema for Category {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Category".into()
+    }
+
+    fn json_schema(_gen: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        let variants = Category::all()
+            .flat_map(|category| {
+                let info = category.info();
+                let aliases = info.aliases.iter().map(move |alias| {
+                    json_schema!({
+                        "description": format!("Alias of {}", info.id),
+                        "type": "string",
+                        "const": alias,
+                    })
+                });
+
+                std::iter::once(json_schema!({
+                    "description": info.description,
+                    "type": "string",
+                    "const": info.id,
+                }))
+                .chain(aliases)
+            })
+            .collect::<Vec<_>>();
+
+        json_schema!({ "oneOf": variants })
+    }
+}
+
+/// Node in the tree of categories
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CategoryNode {
+    pub id: Category,
+    /// Human-readable name
+    pub label: String,
+    pub description: String,
+    /// Number of crates in this category or any of its subcategories
+    pub count: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub children: Vec<CategoryNode>,
+}
+
+impl CategoryNode {
+    /// Build the tree of all categories, using `count` to get the number of crates in each
+    pub fn tree(count: &impl Fn(Category) -> usize) -> Vec<Self> {
+        Category::all()
+            .filter(|category| category.parent().is_none())
+            .map(|category| Self::new(category, count))
+            .collect()
+    }
+
+    fn new(category: Category, count: &impl Fn(Category) -> usize) -> Self {
+        Self {
+            id: category,
+            label: category.label().to_string(),
+            description: category.description().to_string(),
+            count: count(category),
+            children: category
+                .children()
+                .map(|child| Self::new(child, count))
+                .collect(),
+        }
     }
 }
@@ -0,0 +1,269 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::electrical::LogicLevel;
+
+/// Connector or form factor for attaching a board
+///
+/// Standards with a main board and add-ons have a variant for each side, e.g. `Feather` and
+/// `FeatherWing`.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum Connection {
+    /// Adafruit JST-SH 4-pin I2C connector, works with 3.3 V and 5 V
+    StemmaQt,
+    /// SparkFun JST-SH 4-pin I2C connector at 3.3 V, compatible with STEMMA QT
+    Qwiic,
+    /// Adafruit JST-PH connector with I2C, analog or digital signals
+    Stemma,
+    /// Seeed 4-pin connector with I2C, UART, analog or digital signals
+    Grove,
+    /// DFRobot connector with I2C, UART, analog or digital signals
+    Gravity,
+    /// MikroElektronika Click board for a mikroBUS socket
+    MikroBus,
+    /// MikroElektronika mikroBUS socket, which Click boards plug into
+    MikroBusSocket,
+    /// Digilent 6- or 12-pin module with SPI, I2C, UART or GPIO
+    Pmod,
+    /// Digilent Pmod port, which Pmod modules plug into
+    PmodHost,
+    /// Olimex 10-pin module with I2C, SPI and UART at 3.3 V
+    #[serde(rename = "UEXT")]
+    Uext,
+    /// Olimex UEXT connector, which UEXT modules plug into
+    #[serde(rename = "UEXTHost")]
+    UextHost,
+    /// Adafruit Feather main board, which FeatherWings plug into
+    Feather,
+    /// Add-on board stacking onto an Adafruit Feather
+    FeatherWing,
+    /// Raspberry Pi or a board with its 40-pin header, which HATs plug into
+    RaspberryPi,
+    /// Add-on board for the Raspberry Pi 40-pin header
+    RaspberryPiHat,
+    /// Arduino Uno or a board with its headers, which shields plug into
+    Arduino,
+    /// Add-on board for the Arduino Uno headers
+    ArduinoShield,
+    /// BBC micro:bit card edge connector
+    MicrobitEdge,
+    /// Accessory with an edge connector socket for a BBC micro:bit
+    MicrobitAccessory,
+}
+
+/// Side of a connection a board is on
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Role {
+    /// Main board that add-ons plug into
+    Host,
+    /// Board that plugs into a host
+    AddOn,
+    /// Cable connector, any two boards with one can be connected
+    Cable,
+}
+
+/// Physical connector, boards with the same plug can be connected mechanically
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum Plug {
+    JstSh4,
+    JstPh,
+    Grove,
+    Gravity,
+    MikroBus,
+    Pmod,
+    Idc10,
+    FeatherHeaders,
+    RaspberryPiHeader,
+    ArduinoHeaders,
+    MicrobitEdge,
+}
+
+/// Signals carried by a connector
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum Bus {
+    I2C,
+    SPI,
+    UART,
+    Analog,
+    GPIO,
+}
+
+/// Electrical interface of a connector
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ConnectorInterface {
+    pub plug: Plug,
+    pub role: Role,
+    pub buses: &'static [Bus],
+    pub logic_levels: &'static [LogicLevel],
+}
+
+const ALL_BUSES: &[Bus] = &[Bus::I2C, Bus::SPI, Bus::UART, Bus::Analog, Bus::GPIO];
+const V3_3: &[LogicLevel] = &[LogicLevel::V3_3];
+const V3_3_AND_5: &[LogicLevel] = &[LogicLevel::V3_3, LogicLevel::V5];
+
+impl Connection {
+    pub fn all() -> &'static [Self] {
+        &[
+            Connection::StemmaQt,
+            Connection::Qwiic,
+            Connection::Stemma,
+            Connection::Grove,
+            Connection::Gravity,
+            Connection::MikroBus,
+            Connection::MikroBusSocket,
+            Connection::Pmod,
+            Connection::PmodHost,
+            Connection::Uext,
+            Connection::UextHost,
+            Connection::Feather,
+            Connection::FeatherWing,
+            Connection::RaspberryPi,
+            Connection::RaspberryPiHat,
+            Connection::Arduino,
+            Connection::ArduinoShield,
+            Connection::MicrobitEdge,
+            Connection::MicrobitAccessory,
+        ]
+    }
+
+    pub fn interface(&self) -> ConnectorInterface {
+        let (plug, buses, logic_levels) = match self {
+            Connection::StemmaQt => (Plug::JstSh4, &[Bus::I2C][..], V3_3_AND_5),
+            Connection::Qwiic => (Plug::JstSh4, &[Bus::I2C][..], V3_3),
+            Connection::Stemma => (
+                Plug::JstPh,
+                &[Bus::I2C, Bus::Analog, Bus::GPIO][..],
+                V3_3_AND_5,
+            ),
+            Connection::Grove => (
+                Plug::Grove,
+                &[Bus::I2C, Bus::UART, Bus::Analog, Bus::GPIO][..],
+                V3_3_AND_5,
+            ),
+            Connection::Gravity => (
+                Plug::Gravity,
+                &[Bus::I2C, Bus::UART, Bus::Analog, Bus::GPIO][..],
+                V3_3_AND_5,
+            ),
+            Connection::MikroBus | Connection::MikroBusSocket => {
+                (Plug::MikroBus, ALL_BUSES, V3_3_AND_5)
+            }
+            Connection::Pmod | Connection::PmodHost => (
+                Plug::Pmod,
+                &[Bus::I2C, Bus::SPI, Bus::UART, Bus::GPIO][..],
+                V3_3,
+            ),
+            Connection::Uext | Connection::UextHost => {
+                (Plug::Idc10, &[Bus::I2C, Bus::SPI, Bus::UART][..], V3_3)
+            }
+            Connection::Feather | Connection::FeatherWing => {
+                (Plug::FeatherHeaders, ALL_BUSES, V3_3)
+            }
+            Connection::RaspberryPi | Connection::RaspberryPiHat => (
+                Plug::RaspberryPiHeader,
+                &[Bus::I2C, Bus::SPI, Bus::UART, Bus::GPIO][..],
+                V3_3,
+            ),
+            Connection::Arduino | Connection::ArduinoShield => {
+                (Plug::ArduinoHeaders, ALL_BUSES, V3_3_AND_5)
+            }
+            Connection::MicrobitEdge | Connection::MicrobitAccessory => {
+                (Plug::MicrobitEdge, ALL_BUSES, V3_3)
+            }
+        };
+
+        ConnectorInterface {
+            plug,
+            role: self.role(),
+            buses,
+            logic_levels,
+        }
+    }
+
+    pub fn role(&self) -> Role {
+        match self {
+            Connection::StemmaQt
+            | Connection::Qwiic
+            | Connection::Stemma
+            | Connection::Grove
+            | Connection::Gravity => Role::Cable,
+            Connection::MikroBusSocket
+            | Connection::PmodHost
+            | Connection::UextHost
+            | Connection::Feather
+            | Connection::RaspberryPi
+            | Connection::Arduino
+            | Connection::MicrobitEdge => Role::Host,
+            Connection::MikroBus
+            | Connection::Pmod
+            | Connection::Uext
+            | Connection::FeatherWing
+            | Connection::RaspberryPiHat
+            | Connection::ArduinoShield
+            | Connection::MicrobitAccessory => Role::AddOn,
+        }
+    }
+
+    /// Can a board with this connector be plugged into a board with `host`?
+    ///
+    /// Add-ons plug into hosts and cable connectors into each other. Both need the same plug, at
+    /// least one common bus and a common logic level, e.g. Qwiic boards work with STEMMA QT boards
+    /// at 3.3 V.
+    pub fn plugs_into(&self, host: &Connection) -> bool {
+        let a = self.interface();
+        let b = host.interface();
+
+        matches!(
+            (a.role, b.role),
+            (Role::AddOn, Role::Host) | (Role::Cable, Role::Cable)
+        ) && a.plug == b.plug
+            && a.buses.iter().any(|bus| b.buses.contains(bus))
+            && a.logic_levels
+                .iter()
+                .any(|level| b.logic_levels.contains(level))
+    }
+
+    /// All connectors a board with this connector plugs into, including itself for cable connectors
+    pub fn fits_into(&self) -> impl Iterator<Item = Connection> + '_ {
+        Self::all()
+            .iter()
+            .copied()
+            .filter(|host| self.plugs_into(host))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fits_into(connection: Connection) -> Vec<Connection> {
+        connection.fits_into().collect()
+    }
+
+    #[test]
+    fn add_ons_plug_into_hosts_only() {
+        assert_eq!(fits_into(Connection::FeatherWing), [Connection::Feather]);
+        assert!(!fits_into(Connection::Feather).contains(&Connection::FeatherWing));
+        assert_eq!(
+            fits_into(Connection::RaspberryPiHat),
+            [Connection::RaspberryPi]
+        );
+        assert_eq!(fits_into(Connection::ArduinoShield), [Connection::Arduino]);
+        assert!(fits_into(Connection::Arduino).is_empty());
+    }
+
+    #[test]
+    fn cable_connectors_plug_into_each_other() {
+        assert_eq!(
+            fits_into(Connection::Qwiic),
+            [Connection::StemmaQt, Connection::Qwiic]
+        );
+        assert!(fits_into(Connection::StemmaQt).contains(&Connection::Qwiic));
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::{bail, Context};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::units::{Quantity, QuantityRange, Unit};
+
+/// Voltage of the logic signals of a chip
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum LogicLevel {
+    #[serde(rename = "1.8V")]
+    V1_8,
+    #[serde(rename = "3.3V")]
+    V3_3,
+    #[serde(rename = "5V")]
+    V5,
+    /// Inputs accept 5 V signals, even when running at a lower voltage
+    #[serde(rename = "5V-tolerant")]
+    V5Tolerant,
+}
+
+/// Electrical characteristics of a chip
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "ElectricalSpec")]
+pub struct Electrical {
+    /// Lowest and highest supply voltage
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub supply_voltage: Option<QuantityRange>,
+    /// Voltages the logic signals work with
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub logic_levels: Vec<LogicLevel>,
+    /// Typical current drawn while active
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub typical_current: Option<Quantity>,
+    /// Current drawn in the lowest power mode that keeps the chip configured
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sleep_current: Option<Quantity>,
+    /// Lowest and highest ambient temperature the chip can operate in
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub operating_temperature: Option<QuantityRange>,
+}
+
+/// Unvalidated [`Electrical`]
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct ElectricalSpec {
+    /// Lowest and highest supply voltage
+    #[serde(default)]
+    supply_voltage: Option<QuantityRange>,
+    /// Voltages the logic signals work with
+    #[serde(default)]
+    logic_levels: Vec<LogicLevel>,
+    /// Typical current drawn while active
+    #[serde(default)]
+    typical_current: Option<Quantity>,
+    /// Current drawn in the lowest power mode that keeps the chip configured
+    #[serde(default)]
+    sleep_current: Option<Quantity>,
+    /// Lowest and highest ambient temperature the chip can operate in
+    #[serde(default)]
+    operating_temperature: Option<QuantityRange>,
+}
+
+impl TryFrom<ElectricalSpec> for Electrical {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ElectricalSpec) -> Result<Self, Self::Error> {
+        let ElectricalSpec {
+            supply_voltage,
+            logic_levels,
+            typical_current,
+            sleep_current,
+            operating_temperature,
+        } = value;
+
+        if let Some(range) = &supply_voltage {
+            if range.unit() != Unit::Volt {
+                bail!("Supply voltage should be in V, found {range}");
+            }
+        }
+        if let Some(current) = &typical_current {
+            current
+                .expect_unit(Unit::Ampere)
+                .context("Invalid typical current")?;
+        }
+        if let Some(current) = &sleep_current {
+            current
+                .expect_unit(Unit::Ampere)
+                .context("Invalid sleep current")?;
+        }
+        if let Some(range) = &operating_temperature {
+            if range.unit() != Unit::Celsius {
+                bail!("Operating temperature should be in °C, found {range}");
+            }
+        }
+
+        Ok(Self {
+            supply_voltage,
+            logic_levels,
+            typical_current,
+            sleep_current,
+            operating_temperature,
+        })
+    }
+}
+
+/// Supply situations a chip can be used in
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum SupplyClass {
+    /// Runs from and talks to a 1.8 V microcontroller
+    #[serde(rename = "1.8V")]
+    V1_8,
+    /// Runs from and talks to a 3.3 V microcontroller
+    #[serde(rename = "3.3V")]
+    V3_3,
+    /// Runs from and talks to a 5 V microcontroller
+    #[serde(rename = "5V")]
+    V5,
+    /// Runs directly from a CR2032 coin cell, from 3.0 V down to 2.0 V, drawing at most 3 mA while
+    /// active and at most 10 µA while sleeping
+    ///
+    /// Chips without a sleep current have to stay below 10 µA while active.
+    CoinCell,
+}
+
+/// Standard operating temperature ranges
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum TemperatureGrade {
+    /// 0 °C to 70 °C
+    Commercial,
+    /// -40 °C to 85 °C
+    Industrial,
+    /// -40 °C to 125 °C
+    Automotive,
+}
+
+impl TemperatureGrade {
+    pub fn range(&self) -> QuantityRange {
+        let (min, max) = match self {
+            TemperatureGrade::Commercial => (0.0, 70.0),
+            TemperatureGrade::Industrial => (-40.0, 85.0),
+            TemperatureGrade::Automotive => (-40.0, 125.0),
+        };
+        QuantityRange {
+            min: Quantity::new(min, Unit::Celsius),
+            max: Quantity::new(max, Unit::Celsius),
+        }
+    }
+}
+
+impl Electrical {
+    /// Can the chip be powered from and talk to a microcontroller running at `voltage` volts?
+    pub fn works_at(&self, voltage: f64, level: LogicLevel) -> bool {
+        let supplied = self
+            .supply_voltage
+            .is_some_and(|range| range.contains(&Quantity::new(voltage, Unit::Volt)));
+
+        supplied && (self.logic_levels.is_empty() || self.logic_levels.contains(&level))
+    }
+
+    /// Supply situations the chip can be used in
+    pub fn supply_classes(&self) -> Vec<SupplyClass> {
+        let mut classes = vec![];
+
+        if self.works_at(1.8, LogicLevel::V1_8) {
+            classes.push(SupplyClass::V1_8);
+        }
+        if self.works_at(3.3, LogicLevel::V3_3) {
+            classes.push(SupplyClass::V3_3);
+        }
+        if self.works_at(5.0, LogicLevel::V5) {
+            classes.push(SupplyClass::V5);
+        }
+
+        let coin_cell = QuantityRange {
+            min: Quantity::new(2.0, Unit::Volt),
+            max: Quantity::new(3.0, Unit::Volt),
+        };
+        // Unknown currents could be anything, so they rule out a coin cell
+        let low_current = self.typical_current.is_some_and(|active| {
+            let idle = self.sleep_current.unwrap_or(active);
+            active.value <= 3e-3 && idle.value <= 10e-6
+        });
+        if self
+            .supply_voltage
+            .is_some_and(|range| range.covers(&coin_cell))
+            && low_current
+        {
+            classes.push(SupplyClass::CoinCell);
+        }
+
+        classes
+    }
+
+    /// Standard temperature grades the operating temperature range covers
+    pub fn temperature_grades(&self) -> Vec<TemperatureGrade> {
+        let Some(range) = &self.operating_temperature else {
+            return vec![];
+        };
+
+        [
+            TemperatureGrade::Commercial,
+            TemperatureGrade::Industrial,
+            TemperatureGrade::Automotive,
+        ]
+        .into_iter()
+        .filter(|grade| range.covers(&grade.range()))
+        .collect()
+    }
+}
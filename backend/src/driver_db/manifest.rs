@@ -0,0 +1,87 @@
+use anyhow::{bail, Context};
+use semver::Version;
+use serde::Deserialize;
+
+use super::boards::BoardRegistry;
+use super::Driver;
+
+pub mod v0_0_1;
+
+/// Manifest version of the current [`Driver`] model
+pub const CURRENT_VERSION: Version = Version::new(0, 1, 0);
+
+/// Longest line before arrays are split over multiple lines, like taplo's `column_width`
+const COLUMN_WIDTH: usize = 80;
+
+/// Driver description read from a TOML file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    /// Manifest version the file was written in
+    pub version: Version,
+    /// Description migrated to the current manifest version
+    pub driver: Driver,
+}
+
+impl Manifest {
+    /// Parse a driver description of any supported manifest version
+    ///
+    /// Older manifests may reference boards that now live in the board registry, so it is
+    /// needed to migrate them.
+    pub fn parse(source: &str, boards: &BoardRegistry) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            manifest_version: Version,
+        }
+
+        let Versioned { manifest_version } =
+            toml::from_str(source).context("Could not read manifest_version")?;
+
+        let driver = if manifest_version == CURRENT_VERSION {
+            toml::from_str::<Driver>(source)?
+        } else if manifest_version == v0_0_1::VERSION {
+            toml::from_str::<v0_0_1::Driver>(source)
+                .with_context(|| {
+                    format!(
+                        "Invalid manifest version {manifest_version} description, fields added \
+                         since need manifest_version = \"{CURRENT_VERSION}\""
+                    )
+                })?
+                .migrate(boards)?
+        } else {
+            bail!(
+                "Unsupported manifest_version {manifest_version}, the newest is {CURRENT_VERSION}"
+            );
+        };
+
+        Ok(Self {
+            version: manifest_version,
+            driver,
+        })
+    }
+
+    /// Whether the file was written in an older manifest version
+    pub fn is_outdated(&self) -> bool {
+        self.version != CURRENT_VERSION
+    }
+}
+
+/// Rewrite `source` in the current manifest version, or `None` if it already is
+///
+/// Only what changed between the versions is rewritten, all other values stay as they were
+/// written. The result is checked to describe the same driver as `source`.
+pub fn migrate_source(source: &str, boards: &BoardRegistry) -> anyhow::Result<Option<String>> {
+    let manifest = Manifest::parse(source, boards)?;
+    let migrated = if !manifest.is_outdated() {
+        return Ok(None);
+    } else if manifest.version == v0_0_1::VERSION {
+        v0_0_1::migrate_source(source, boards)?
+    } else {
+        unreachable!("Manifest::parse only accepts supported versions");
+    };
+
+    let reread = Manifest::parse(&migrated, boards).context("Migrated description is invalid")?;
+    if reread.driver != manifest.driver {
+        bail!("Migrated description differs from the original");
+    }
+    Ok(Some(migrated))
+}
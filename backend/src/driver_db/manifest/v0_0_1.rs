@@ -0,0 +1,394 @@
+//! Driver descriptions from before the board registry, with `manifest_version = "0.0.1"`
+//!
+//! The types here are a frozen copy of the 0.0.1 format, so changes to the current types don't
+//! change how these files are read. Values with their own syntax, like categories or packages,
+//! are kept as written and checked by the current types in [`Driver::migrate`].
+
+use anyhow::{bail, Context};
+use semver::Version;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use toml::Value;
+use url::Url;
+
+use super::CURRENT_VERSION;
+use crate::driver_db::boards::BoardRegistry;
+use crate::driver_db::part_numbers::{self, PartNumberSpec};
+
+pub const VERSION: Version = Version::new(0, 0, 1);
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Driver {
+    pub manifest_version: Version,
+    pub meta: Meta,
+    #[serde(default)]
+    pub dev_boards: Vec<DevBoard>,
+    #[serde(default)]
+    pub interfaces: Interfaces,
+    #[serde(default)]
+    pub resources: Vec<Resource>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Meta {
+    pub names: Vec<String>,
+    pub manufacturer: String,
+    #[serde(default)]
+    pub datasheets: Vec<Url>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub part_numbers: Vec<String>,
+    #[serde(default)]
+    pub kicad_symbol: Vec<String>,
+    #[serde(default)]
+    pub packages: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Interfaces {
+    #[serde(default)]
+    pub i2c: Option<I2c>,
+    #[serde(default)]
+    pub spi: Option<Spi>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct I2c {
+    pub addrs: Vec<u8>,
+    pub interrupt: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Spi {
+    pub bus_type: SpiDeviceType,
+    pub interrupt: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum SpiDeviceType {
+    SpiBus,
+    SpiDevice,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Resource {
+    pub title: String,
+    pub link: Url,
+}
+
+/// Development board described in the driver itself
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DevBoard {
+    pub name: String,
+    pub manufacturer: BoardManufacturer,
+    pub link: Url,
+    /// Connectors of the board, the registry board replacing it has its own
+    pub connections: Vec<Connection>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum BoardManufacturer {
+    Adafruit,
+    Sparkfun,
+    Mikroe,
+    #[serde(rename = "BBC")]
+    Bbc,
+    DFRobot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Connection {
+    StemmaQt,
+    MikroBus,
+}
+
+/// Read a value kept as written with the current type
+fn convert<T: DeserializeOwned>(value: impl Into<Value>, what: &str) -> anyhow::Result<T> {
+    let value = value.into();
+    value
+        .clone()
+        .try_into()
+        .with_context(|| format!("Invalid {what}: {value}"))
+}
+
+impl Meta {
+    fn migrate(self) -> anyhow::Result<crate::driver_db::Meta> {
+        let part_numbers = part_numbers::parse(
+            self.part_numbers
+                .into_iter()
+                .map(PartNumberSpec::Number)
+                .collect(),
+        )?;
+
+        Ok(crate::driver_db::Meta {
+            names: self.names,
+            manufacturer: convert(self.manufacturer, "manufacturer")?,
+            datasheets: self.datasheets,
+            categories: self
+                .categories
+                .into_iter()
+                .map(|category| convert(category, "category"))
+                .collect::<anyhow::Result<_>>()?,
+            part_numbers,
+            kicad_symbol: self.kicad_symbol,
+            packages: self
+                .packages
+                .into_iter()
+                .map(|package| convert(package, "package"))
+                .collect::<anyhow::Result<_>>()?,
+            measurements: vec![],
+            electrical: None,
+        })
+    }
+}
+
+impl Interfaces {
+    fn migrate(self) -> crate::driver_db::Interfaces {
+        crate::driver_db::Interfaces {
+            i2c: self.i2c.map(|i2c| crate::driver_db::I2c {
+                addrs: i2c.addrs,
+                interrupt: i2c.interrupt,
+            }),
+            spi: self.spi.map(|spi| crate::driver_db::Spi {
+                bus_type: match spi.bus_type {
+                    SpiDeviceType::SpiBus => crate::driver_db::SpiDeviceType::SpiBus,
+                    SpiDeviceType::SpiDevice => crate::driver_db::SpiDeviceType::SpiDevice,
+                },
+                interrupt: spi.interrupt,
+            }),
+        }
+    }
+}
+
+impl Driver {
+    /// Convert to the current manifest version
+    ///
+    /// Inline boards are replaced by the id of the registry board with the same product page.
+    pub fn migrate(self, boards: &BoardRegistry) -> anyhow::Result<crate::driver_db::Driver> {
+        let mut dev_boards = Vec::with_capacity(self.dev_boards.len());
+        for board in self.dev_boards {
+            let Some(registered) = boards.boards.values().find(|b| b.link == board.link) else {
+                bail!(
+                    "Board {:?} ({}) is not in the board registry, add it to `boards` first",
+                    board.name,
+                    board.link
+                );
+            };
+            if !dev_boards.contains(&registered.id) {
+                dev_boards.push(registered.id.clone());
+            }
+        }
+
+        Ok(crate::driver_db::Driver {
+            manifest_version: CURRENT_VERSION,
+            meta: self.meta.migrate()?,
+            dev_boards,
+            interfaces: self.interfaces.migrate(),
+            resources: self
+                .resources
+                .into_iter()
+                .map(|resource| crate::driver_db::Resource {
+                    title: resource.title,
+                    link: resource.link,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Rewrite a 0.0.1 description in the current version
+///
+/// Only `manifest_version` and the boards are rewritten, everything else stays as written.
+pub fn migrate_source(source: &str, boards: &BoardRegistry) -> anyhow::Result<String> {
+    let driver: Driver = toml::from_str(source)?;
+    let dev_boards = driver.migrate(boards)?.dev_boards;
+
+    let mut out = String::new();
+    let mut top_level = true;
+    let mut scanner = Scanner::default();
+    let mut skipping_section = false;
+    let mut skipping_value = false;
+    let mut found_version = false;
+
+    for line in source.split_inclusive('\n') {
+        let continued = scanner.in_value();
+        scanner.scan(line);
+        if continued {
+            if !skipping_section && !skipping_value {
+                out.push_str(line);
+            }
+            continue;
+        }
+        skipping_value = false;
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') {
+            top_level = false;
+            let path = trimmed.trim_start_matches('[');
+            let path = path[..path.find(']').unwrap_or(path.len())].trim();
+            let was_skipping = skipping_section;
+            skipping_section = path == "dev_boards" || path.starts_with("dev_boards.");
+            if was_skipping && !skipping_section && !out.is_empty() && !out.ends_with("\n\n") {
+                out.push('\n');
+            }
+        }
+        if skipping_section {
+            continue;
+        }
+
+        if top_level {
+            match key(trimmed) {
+                Some("manifest_version") => {
+                    if !dev_boards.is_empty() {
+                        out.push_str(&dev_boards_entry(&dev_boards));
+                    }
+                    out.push_str(&format!("manifest_version = \"{CURRENT_VERSION}\"\n"));
+                    found_version = true;
+                    continue;
+                }
+                Some("dev_boards") => {
+                    skipping_value = true;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        out.push_str(line);
+    }
+
+    if scanner.in_value() {
+        bail!("Unterminated value at the end of the description");
+    }
+    if !found_version {
+        bail!("manifest_version has to be a top-level key");
+    }
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+    Ok(out)
+}
+
+/// Key assigned on a line like `key = value`
+fn key(line: &str) -> Option<&str> {
+    let (key, _) = line.split_once('=')?;
+    Some(key.trim().trim_matches('"'))
+}
+
+/// Tracks values spanning several lines, like arrays, inline tables and multi-line strings
+#[derive(Debug, Default)]
+struct Scanner {
+    /// Depth of brackets and braces left open
+    depth: i32,
+    /// Quote of a multi-line string left open
+    multiline: Option<u8>,
+}
+
+impl Scanner {
+    /// Whether the next line continues a value started on a previous line
+    fn in_value(&self) -> bool {
+        self.depth > 0 || self.multiline.is_some()
+    }
+
+    /// Advance over `line`, ignoring brackets in strings and comments
+    fn scan(&mut self, line: &str) {
+        // All delimiters are ASCII, so bytes of multi-byte characters never match them
+        let bytes = line.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            if let Some(quote) = self.multiline {
+                if quote == b'"' && bytes[i] == b'\\' {
+                    i += 2;
+                } else if bytes[i..].starts_with(&[quote; 3]) {
+                    // Up to two quotes before the closing ones belong to the string
+                    while bytes.get(i) == Some(&quote) {
+                        i += 1;
+                    }
+                    self.multiline = None;
+                } else {
+                    i += 1;
+                }
+                continue;
+            }
+
+            match bytes[i] {
+                quote @ (b'"' | b'\'') if bytes[i..].starts_with(&[quote; 3]) => {
+                    self.multiline = Some(quote);
+                    i += 3;
+                }
+                quote @ (b'"' | b'\'') => {
+                    i += 1;
+                    while i < bytes.len() && bytes[i] != quote {
+                        i += if quote == b'"' && bytes[i] == b'\\' {
+                            2
+                        } else {
+                            1
+                        };
+                    }
+                    i += 1;
+                }
+                b'[' | b'{' => {
+                    self.depth += 1;
+                    i += 1;
+                }
+                b']' | b'}' => {
+                    self.depth -= 1;
+                    i += 1;
+                }
+                b'#' => break,
+                _ => i += 1,
+            }
+        }
+    }
+}
+
+/// `dev_boards` line with the ids of the registry boards, split like taplo does for long arrays
+fn dev_boards_entry(ids: &[String]) -> String {
+    let ids: Vec<_> = ids
+        .iter()
+        .map(|id| Value::from(id.as_str()).to_string())
+        .collect();
+    let line = format!("dev_boards = [{}]\n", ids.join(", "));
+    if line.len() <= super::COLUMN_WIDTH + 1 {
+        return line;
+    }
+    let mut out = String::from("dev_boards = [\n");
+    for id in ids {
+        out.push_str(&format!("    {id},\n"));
+    }
+    out.push_str("]\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn continued_lines(source: &str) -> Vec<bool> {
+        let mut scanner = Scanner::default();
+        source
+            .lines()
+            .map(|line| {
+                let continued = scanner.in_value();
+                scanner.scan(line);
+                continued
+            })
+            .collect()
+    }
+
+    #[test]
+    fn multiline_strings_continue_values() {
+        let source = "name = \"\"\"\n[[dev_boards]]\nquote \\\"\"\" [\n\"\"\"\n[meta]\n";
+        assert_eq!(continued_lines(source), [false, true, true, true, false]);
+
+        let source = "title = '''\n[meta] \\'''\nnames = [\"a\", # ]\n  \"b\"]\n";
+        assert_eq!(continued_lines(source), [false, true, false, true]);
+    }
+}
@@ -0,0 +1,183 @@
+use anyhow::{bail, Context};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::units::{Quantity, QuantityRange, Unit};
+
+/// Physical quantity measured by a sensor
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum MeasuredQuantity {
+    /// Temperature in °C
+    Temperature,
+    /// Relative humidity in %RH
+    Humidity,
+    /// Air pressure in Pa
+    Pressure,
+    /// CO2 concentration in ppm
+    CO2,
+    /// Equivalent CO2 concentration calculated from VOC values in ppm
+    #[serde(rename = "eCO2")]
+    ECO2,
+    /// Total volatile organic compounds in ppm
+    TVOC,
+    /// Acceleration in g
+    Acceleration,
+    /// Angular rate in dps
+    AngularRate,
+    /// Magnetic field strength in T
+    MagneticField,
+    /// Illuminance in lx
+    Illuminance,
+    /// Distance in m
+    Distance,
+    /// Electric current in A
+    Current,
+    /// Electric voltage in V
+    Voltage,
+    /// Electric power in W
+    Power,
+}
+
+impl MeasuredQuantity {
+    /// Unit in which this quantity is measured
+    pub fn unit(&self) -> Unit {
+        match self {
+            MeasuredQuantity::Temperature => Unit::Celsius,
+            MeasuredQuantity::Humidity => Unit::RelativeHumidity,
+            MeasuredQuantity::Pressure => Unit::Pascal,
+            MeasuredQuantity::CO2 => Unit::Ppm,
+            MeasuredQuantity::ECO2 => Unit::Ppm,
+            MeasuredQuantity::TVOC => Unit::Ppm,
+            MeasuredQuantity::Acceleration => Unit::StandardGravity,
+            MeasuredQuantity::AngularRate => Unit::DegreesPerSecond,
+            MeasuredQuantity::MagneticField => Unit::Tesla,
+            MeasuredQuantity::Illuminance => Unit::Lux,
+            MeasuredQuantity::Distance => Unit::Meter,
+            MeasuredQuantity::Current => Unit::Ampere,
+            MeasuredQuantity::Voltage => Unit::Volt,
+            MeasuredQuantity::Power => Unit::Watt,
+        }
+    }
+}
+
+/// Specification of a quantity measured by a sensor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "MeasurementSpec")]
+pub struct Measurement {
+    /// Quantity that is measured
+    pub quantity: MeasuredQuantity,
+    /// Lowest and highest value that can be measured
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub range: Option<QuantityRange>,
+    /// Smallest change that can be measured
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub resolution: Option<Quantity>,
+    /// Maximum error of a measurement, either absolute or in %
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub accuracy: Option<Quantity>,
+    /// Highest rate at which measurements can be taken
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sample_rate: Option<Quantity>,
+    /// Current drawn while measuring
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub supply_current: Option<Quantity>,
+}
+
+/// Unvalidated [`Measurement`]
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct MeasurementSpec {
+    /// Quantity that is measured
+    quantity: MeasuredQuantity,
+    /// Lowest and highest value that can be measured
+    #[serde(default)]
+    range: Option<QuantityRange>,
+    /// Smallest change that can be measured
+    #[serde(default)]
+    resolution: Option<Quantity>,
+    /// Maximum error of a measurement, either absolute or in %
+    #[serde(default)]
+    accuracy: Option<Quantity>,
+    /// Highest rate at which measurements can be taken
+    #[serde(default)]
+    sample_rate: Option<Quantity>,
+    /// Current drawn while measuring
+    #[serde(default)]
+    supply_current: Option<Quantity>,
+}
+
+impl TryFrom<MeasurementSpec> for Measurement {
+    type Error = anyhow::Error;
+
+    fn try_from(value: MeasurementSpec) -> Result<Self, Self::Error> {
+        let MeasurementSpec {
+            quantity,
+            range,
+            resolution,
+            accuracy,
+            sample_rate,
+            supply_current,
+        } = value;
+        let unit = quantity.unit();
+
+        if let Some(range) = &range {
+            if range.unit() != unit {
+                bail!("Range of {quantity:?} should be in {unit}, found {range}");
+            }
+        }
+        if let Some(resolution) = &resolution {
+            resolution
+                .expect_unit(unit)
+                .with_context(|| format!("Invalid resolution of {quantity:?}"))?;
+        }
+        if let Some(accuracy) = &accuracy {
+            if accuracy.unit != Unit::Percent {
+                accuracy
+                    .expect_unit(unit)
+                    .with_context(|| format!("Invalid accuracy of {quantity:?}"))?;
+            }
+        }
+        if let Some(sample_rate) = &sample_rate {
+            sample_rate
+                .expect_unit(Unit::Hertz)
+                .with_context(|| format!("Invalid sample rate of {quantity:?}"))?;
+        }
+        if let Some(supply_current) = &supply_current {
+            supply_current
+                .expect_unit(Unit::Ampere)
+                .with_context(|| format!("Invalid supply current of {quantity:?}"))?;
+        }
+
+        Ok(Self {
+            quantity,
+            range,
+            resolution,
+            accuracy,
+            sample_rate,
+            supply_current,
+        })
+    }
+}
+
+impl Measurement {
+    /// Absolute accuracy of this measurement, if it is not given in %
+    pub fn absolute_accuracy(&self) -> Option<Quantity> {
+        self.accuracy
+            .filter(|accuracy| accuracy.unit == self.quantity.unit())
+    }
+
+    /// Does this measurement cover all of `range` and is it at least as accurate as `accuracy`?
+    ///
+    /// `accuracy` is absolute, measurements with an accuracy in % or none at all don't meet it.
+    pub fn meets(&self, range: Option<&QuantityRange>, accuracy: Option<&Quantity>) -> bool {
+        let covers = range.is_none_or(|range| self.range.is_some_and(|own| own.covers(range)));
+        let accurate = accuracy.is_none_or(|accuracy| {
+            self.absolute_accuracy().is_some_and(|own| {
+                own.unit == accuracy.unit && own.value.abs() <= accuracy.value.abs()
+            })
+        });
+        covers && accurate
+    }
+}
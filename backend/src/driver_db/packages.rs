@@ -7,26 +7,192 @@ use anyhow::{bail, Context};
 use schemars::{json_schema, JsonSchema};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq)]
+use super::units::{Quantity, Unit};
+
+/// Package or footprint of a chip
+///
+/// Written like the KiCad footprint names, e.g. `SOIC-8`, `SOT-23-5`, `QFN-32-1EP_5x5mm_P0.5mm`
+/// or `TSSOP-28_4.4x9.7mm_P0.65mm`.
+#[derive(Clone, PartialEq)]
 pub struct Package {
-    pub ty: PackageType,
-    pub pins: u8,
+    pub family: PackageFamily,
+    /// Sub-type within the family, e.g. the `23` of `SOT-23`
+    pub variant: Option<String>,
+    pub pins: u16,
+    /// Distance between the centers of neighbouring pins, if not the default of the family
+    pub pitch: Option<Quantity>,
+    /// Width and length of the body, without leads
+    pub body_size: Option<[Quantity; 2]>,
+    /// Has a thermal or ground pad on the bottom that needs to be soldered
+    pub exposed_pad: bool,
+}
+
+/// Variants whose pin count may be left out, with their pin count
+const DEFAULT_PINS: &[(PackageFamily, &str, u16)] = &[
+    (PackageFamily::SOT, "23", 3),
+    (PackageFamily::SOT, "89", 3),
+    (PackageFamily::SOT, "143", 4),
+    (PackageFamily::SOT, "223", 4),
+    (PackageFamily::SOT, "323", 3),
+    (PackageFamily::SOT, "353", 5),
+    (PackageFamily::SOT, "363", 6),
+    (PackageFamily::SOT, "563", 6),
+    (PackageFamily::SOT, "583", 8),
+    (PackageFamily::TO, "92", 3),
+    (PackageFamily::TO, "126", 3),
+    (PackageFamily::TO, "220", 3),
+    (PackageFamily::TO, "247", 3),
+    (PackageFamily::TO, "252", 3),
+    (PackageFamily::TO, "263", 3),
+];
+
+impl Package {
+    /// Pitch of this package, falling back to the usual pitch of its family
+    pub fn pitch(&self) -> Option<Quantity> {
+        self.pitch.or_else(|| {
+            Some(Quantity::new(
+                self.family.default_pitch_mm()? * 1e-3,
+                Unit::Meter,
+            ))
+        })
+    }
+
+    /// Can this package be soldered with a soldering iron, without hot air or reflow?
+    ///
+    /// Only packages with leads and a pitch of at least 0.5 mm qualify.
+    pub fn hand_solderable(&self) -> bool {
+        let pitch_ok = self
+            .pitch()
+            .is_none_or(|pitch| pitch.value >= 0.5e-3 - 1e-9);
+
+        match self.family.style() {
+            PackageStyle::ThroughHole | PackageStyle::Module => true,
+            PackageStyle::Leaded => pitch_ok && !self.exposed_pad,
+            PackageStyle::NoLead | PackageStyle::BallGrid => false,
+        }
+    }
+
+    fn default_pins(&self) -> Option<u16> {
+        DEFAULT_PINS
+            .iter()
+            .find(|(family, variant, _)| {
+                *family == self.family && Some(*variant) == self.variant.as_deref()
+            })
+            .map(|(_, _, pins)| *pins)
+    }
+}
+
+/// Parse a length like `4.4mm` or `4.4` into a quantity in meters
+fn parse_mm(s: &str) -> anyhow::Result<Quantity> {
+    let value: f64 = s
+        .strip_suffix("mm")
+        .unwrap_or(s)
+        .parse()
+        .with_context(|| format!("Invalid length in package: {s:?}"))?;
+    Ok(Quantity::new(value * 1e-3, Unit::Meter))
+}
+
+fn fmt_mm(quantity: &Quantity) -> String {
+    let value = format!("{:.3}", quantity.value * 1e3);
+    value
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
 }
 
 impl FromStr for Package {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (ty, pins) = s.split_once("-").context("Package is missing pin count")?;
-        let ty = ty.parse()?;
-        let pins = pins.parse()?;
-        Ok(Package { ty, pins })
+        let mut parts = s.split(['_', ' ']).filter(|part| !part.is_empty());
+        let name = parts.next().context("Package is empty")?;
+
+        let mut tokens = name.split('-');
+        let family: PackageFamily = tokens.next().unwrap_or_default().parse()?;
+
+        let mut exposed_pad = false;
+        let mut numbers = vec![];
+        for token in tokens {
+            match token {
+                "EP" | "1EP" => exposed_pad = true,
+                _ => numbers.push(token),
+            }
+        }
+
+        let mut package = Package {
+            family,
+            variant: None,
+            pins: 0,
+            pitch: None,
+            body_size: None,
+            exposed_pad,
+        };
+
+        match numbers.as_slice() {
+            [variant, pins] => {
+                package.variant = Some(variant.to_string());
+                package.pins = pins
+                    .parse()
+                    .with_context(|| format!("Invalid pin count in package: {s:?}"))?;
+            }
+            [number] => {
+                package.variant = Some(number.to_string());
+                match package.default_pins() {
+                    Some(pins) => package.pins = pins,
+                    None if family.has_variants() => bail!(
+                        "Unknown {family} variant {number}, add the pin count like {family}-{number}-<pins>: {s:?}"
+                    ),
+                    None => {
+                        package.variant = None;
+                        package.pins = number
+                            .parse()
+                            .with_context(|| format!("Invalid pin count in package: {s:?}"))?;
+                    }
+                }
+            }
+            [] => bail!("Package is missing pin count: {s:?}"),
+            _ => bail!("Package has too many parts: {s:?}"),
+        }
+
+        for part in parts {
+            if let Some(pitch) = part.strip_prefix('P') {
+                package.pitch = Some(parse_mm(pitch)?);
+            } else if let Some((width, length)) = part.split_once('x') {
+                package.body_size = Some([parse_mm(width)?, parse_mm(length)?]);
+            } else {
+                bail!("Unknown part {part:?} in package: {s:?}");
+            }
+        }
+
+        Ok(package)
     }
 }
 
 impl Display for Package {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}-{}", self.ty, self.pins)
+        write!(f, "{}", self.family)?;
+        if let Some(variant) = &self.variant {
+            write!(f, "-{variant}")?;
+        }
+        if self.default_pins() != Some(self.pins) {
+            write!(f, "-{}", self.pins)?;
+        }
+        if self.exposed_pad {
+            write!(f, "-1EP")?;
+        }
+        if let Some([width, length]) = &self.body_size {
+            write!(f, "_{}x{}mm", fmt_mm(width), fmt_mm(length))?;
+        }
+        if let Some(pitch) = &self.pitch {
+            write!(f, "_P{}mm", fmt_mm(pitch))?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Package {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
     }
 }
 
@@ -55,65 +221,187 @@ impl JsonSchema for Package {
     }
 
     fn json_schema(_gen: &mut schemars::SchemaGenerator) -> schemars::Schema {
-        let packages = PackageType::all()
+        let families = PackageFamily::all()
             .iter()
-            .map(|ty| ty.to_string())
+            .flat_map(|family| std::iter::once(family.name()).chain(family.aliases().to_vec()))
             .collect::<Vec<_>>()
             .join("|");
-        let regex = format!("^({packages})-\\d+$");
-        json_schema!({"type": "string", "pattern": regex})
+        let regex = format!(
+            r"^({families})(-[0-9]+){{1,2}}(-1?EP)?([_ ][0-9.]+x[0-9.]+mm)?([_ ]P[0-9.]+mm)?$"
+        );
+        json_schema!({
+            "description": "Package name like the KiCad footprint names, e.g. `SOIC-8`, `SOT-23-5` or `QFN-32-1EP_5x5mm_P0.5mm`",
+            "type": "string",
+            "pattern": regex,
+        })
     }
 }
 
+/// How a package connects to the board
 #[derive(
-    Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
 )]
-pub enum PackageType {
-    TSSOP,
-    LGA,
-    HVQFN,
+pub enum PackageStyle {
+    /// Leads go through holes in the board
+    ThroughHole,
+    /// Surface mount with leads sticking out of the body
+    Leaded,
+    /// Surface mount with pads below or at the edge of the body
+    NoLead,
+    /// Surface mount with a grid of solder balls below the body
+    BallGrid,
+    /// Pre-assembled board with pin headers or castellated edges
+    Module,
+}
+
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum PackageFamily {
+    PDIP,
+    SIP,
+    TO,
     SOIC,
-    SOT,
-    SSOP,
     SO,
-    PDIP,
+    SOP,
+    SSOP,
+    TSSOP,
+    MSOP,
+    SOT,
+    SC70,
+    QFP,
+    LQFP,
+    TQFP,
+    QFN,
+    HVQFN,
+    DFN,
+    LGA,
+    BGA,
+    WLCSP,
+    Module,
 }
 
-impl PackageType {
+impl PackageFamily {
     pub fn all() -> &'static [Self] {
         &[
-            PackageType::TSSOP,
-            PackageType::LGA,
-            PackageType::HVQFN,
-            PackageType::SOIC,
-            PackageType::SOT,
-            PackageType::SSOP,
-            PackageType::SO,
-            PackageType::PDIP,
+            PackageFamily::PDIP,
+            PackageFamily::SIP,
+            PackageFamily::TO,
+            PackageFamily::SOIC,
+            PackageFamily::SO,
+            PackageFamily::SOP,
+            PackageFamily::SSOP,
+            PackageFamily::TSSOP,
+            PackageFamily::MSOP,
+            PackageFamily::SOT,
+            PackageFamily::SC70,
+            PackageFamily::QFP,
+            PackageFamily::LQFP,
+            PackageFamily::TQFP,
+            PackageFamily::QFN,
+            PackageFamily::HVQFN,
+            PackageFamily::DFN,
+            PackageFamily::LGA,
+            PackageFamily::BGA,
+            PackageFamily::WLCSP,
+            PackageFamily::Module,
         ]
     }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PackageFamily::PDIP => "PDIP",
+            PackageFamily::SIP => "SIP",
+            PackageFamily::TO => "TO",
+            PackageFamily::SOIC => "SOIC",
+            PackageFamily::SO => "SO",
+            PackageFamily::SOP => "SOP",
+            PackageFamily::SSOP => "SSOP",
+            PackageFamily::TSSOP => "TSSOP",
+            PackageFamily::MSOP => "MSOP",
+            PackageFamily::SOT => "SOT",
+            PackageFamily::SC70 => "SC70",
+            PackageFamily::QFP => "QFP",
+            PackageFamily::LQFP => "LQFP",
+            PackageFamily::TQFP => "TQFP",
+            PackageFamily::QFN => "QFN",
+            PackageFamily::HVQFN => "HVQFN",
+            PackageFamily::DFN => "DFN",
+            PackageFamily::LGA => "LGA",
+            PackageFamily::BGA => "BGA",
+            PackageFamily::WLCSP => "WLCSP",
+            PackageFamily::Module => "Module",
+        }
+    }
+
+    /// Other names that are accepted for this family
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            PackageFamily::PDIP => &["DIP"],
+            PackageFamily::MSOP => &["VSSOP"],
+            PackageFamily::DFN => &["SON"],
+            PackageFamily::WLCSP => &["CSP"],
+            _ => &[],
+        }
+    }
+
+    pub fn style(&self) -> PackageStyle {
+        match self {
+            PackageFamily::PDIP | PackageFamily::SIP | PackageFamily::TO => {
+                PackageStyle::ThroughHole
+            }
+            PackageFamily::SOIC
+            | PackageFamily::SO
+            | PackageFamily::SOP
+            | PackageFamily::SSOP
+            | PackageFamily::TSSOP
+            | PackageFamily::MSOP
+            | PackageFamily::SOT
+            | PackageFamily::SC70
+            | PackageFamily::QFP
+            | PackageFamily::LQFP
+            | PackageFamily::TQFP => PackageStyle::Leaded,
+            PackageFamily::QFN | PackageFamily::HVQFN | PackageFamily::DFN | PackageFamily::LGA => {
+                PackageStyle::NoLead
+            }
+            PackageFamily::BGA | PackageFamily::WLCSP => PackageStyle::BallGrid,
+            PackageFamily::Module => PackageStyle::Module,
+        }
+    }
+
+    /// Are the numbers in package names of this family variant names like `SOT-23`, not pin counts?
+    pub fn has_variants(&self) -> bool {
+        matches!(self, PackageFamily::SOT | PackageFamily::TO)
+    }
+
+    /// Most common pitch in mm, for families that have one
+    fn default_pitch_mm(&self) -> Option<f64> {
+        match self {
+            PackageFamily::PDIP | PackageFamily::SIP => Some(2.54),
+            PackageFamily::SOIC | PackageFamily::SO => Some(1.27),
+            PackageFamily::SSOP | PackageFamily::TSSOP | PackageFamily::MSOP => Some(0.65),
+            PackageFamily::SC70 => Some(0.65),
+            PackageFamily::SOT => Some(0.95),
+            PackageFamily::LQFP | PackageFamily::TQFP => Some(0.5),
+            _ => None,
+        }
+    }
 }
 
-impl FromStr for PackageType {
+impl FromStr for PackageFamily {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "TSSOP" => Ok(PackageType::TSSOP),
-            "LGA" => Ok(PackageType::LGA),
-            "HVQFN" => Ok(PackageType::HVQFN),
-            "SOIC" => Ok(PackageType::SOIC),
-            "SOT" => Ok(PackageType::SOT),
-            "SSOP" => Ok(PackageType::SSOP),
-            "SO" => Ok(PackageType::SO),
-            "PDIP" => Ok(PackageType::PDIP),
-            _ => bail!("Unknown package type: {s}"),
-        }
+        PackageFamily::all()
+            .iter()
+            .find(|family| family.name() == s || family.aliases().contains(&s))
+            .copied()
+            .with_context(|| format!("Unknown package family: {s}"))
     }
 }
 
-impl Display for PackageType {
+impl Display for PackageFamily {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Debug::fmt(self, f)
+        f.write_str(self.name())
     }
 }
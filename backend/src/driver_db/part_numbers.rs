@@ -0,0 +1,185 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use anyhow::bail;
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::electrical::TemperatureGrade;
+use super::packages::Package;
+
+/// Orderable variant of a chip
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PartNumber {
+    /// Part number as used by the manufacturer and distributors, e.g. `INA219AIDCNR`
+    pub number: String,
+    /// Package and pin count of this variant
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub package: Option<Package>,
+    /// Temperature range this variant is specified for
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub temperature_grade: Option<TemperatureGrade>,
+    /// Production status of this variant
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub lifecycle: Option<Lifecycle>,
+    /// End of the part number that only selects packaging like reel size, e.g. `R` or ` T&R`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ordering_suffix: Option<String>,
+}
+
+/// Production status of a part
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum Lifecycle {
+    /// In production and recommended for new designs
+    Active,
+    /// In production, but not recommended for new designs
+    #[serde(alias = "NRND")]
+    NotRecommended,
+    /// Can only be ordered until a final date
+    LastTimeBuy,
+    /// No longer produced
+    Obsolete,
+}
+
+/// Part number as written in a driver description, either just the number or a table
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum PartNumberSpec {
+    Number(String),
+    Detailed(PartNumber),
+}
+
+impl From<PartNumberSpec> for PartNumber {
+    fn from(value: PartNumberSpec) -> Self {
+        match value {
+            PartNumberSpec::Number(number) => PartNumber {
+                number,
+                package: None,
+                temperature_grade: None,
+                lifecycle: None,
+                ordering_suffix: None,
+            },
+            PartNumberSpec::Detailed(part) => part,
+        }
+    }
+}
+
+impl PartNumber {
+    /// Part number without the ordering suffix
+    pub fn base_number(&self) -> &str {
+        self.ordering_suffix
+            .as_deref()
+            .and_then(|suffix| self.number.strip_suffix(suffix))
+            .unwrap_or(&self.number)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.number.is_empty() {
+            bail!("Part number is empty");
+        }
+        if self.number.trim() != self.number {
+            bail!("Part number {:?} has surrounding whitespace", self.number);
+        }
+        if let Some(suffix) = &self.ordering_suffix {
+            if suffix.is_empty() || !self.number.ends_with(suffix.as_str()) {
+                bail!(
+                    "Ordering suffix {suffix:?} is not the end of part number {:?}",
+                    self.number
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge a repeated entry for the same part number, failing if they contradict each other
+    fn merge(&mut self, other: PartNumber) -> anyhow::Result<()> {
+        fn merge_field<T: PartialEq + std::fmt::Debug>(
+            number: &str,
+            field: &str,
+            a: &mut Option<T>,
+            b: Option<T>,
+        ) -> anyhow::Result<()> {
+            match (&a, b) {
+                (Some(a), Some(b)) if *a != b => {
+                    bail!("Part number {number:?} is listed with {field} {a:?} and {b:?}")
+                }
+                (None, b) => *a = b,
+                _ => {}
+            }
+            Ok(())
+        }
+
+        let number = &self.number;
+        merge_field(number, "package", &mut self.package, other.package)?;
+        merge_field(
+            number,
+            "temperature grade",
+            &mut self.temperature_grade,
+            other.temperature_grade,
+        )?;
+        merge_field(number, "lifecycle", &mut self.lifecycle, other.lifecycle)?;
+        merge_field(
+            number,
+            "ordering suffix",
+            &mut self.ordering_suffix,
+            other.ordering_suffix,
+        )?;
+        Ok(())
+    }
+}
+
+/// Parse a list of part numbers, merging repeated entries and sorting them by number
+pub fn parse(specs: Vec<PartNumberSpec>) -> anyhow::Result<Vec<PartNumber>> {
+    let mut parts: Vec<PartNumber> = Vec::with_capacity(specs.len());
+    let mut by_number: HashMap<String, usize> = HashMap::new();
+
+    for part in specs.into_iter().map(PartNumber::from) {
+        part.validate()?;
+
+        match by_number.get(&part.number) {
+            Some(&i) => parts[i].merge(part)?,
+            None => {
+                by_number.insert(part.number.clone(), parts.len());
+                parts.push(part);
+            }
+        }
+    }
+
+    parts.sort_by(|a, b| a.number.cmp(&b.number));
+    Ok(parts)
+}
+
+/// Deserialize a list of part numbers with [`parse`]
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<PartNumber>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let specs = Vec::<PartNumberSpec>::deserialize(deserializer)?;
+    parse(specs).map_err(serde::de::Error::custom)
+}
+
+/// Schema of a list of part numbers, for `#[schemars(with = "PartNumbers")]`
+///
+/// Driver descriptions may give just the number, serialized part numbers are always a [`PartNumber`].
+pub struct PartNumbers;
+
+impl JsonSchema for PartNumbers {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "PartNumbers".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        if generator.contract().is_deserialize() {
+            Vec::<PartNumberSpec>::json_schema(generator)
+        } else {
+            Vec::<PartNumber>::json_schema(generator)
+        }
+    }
+}
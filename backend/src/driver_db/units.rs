@@ -0,0 +1,371 @@
+use std::{
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+use anyhow::{bail, Context};
+use schemars::{json_schema, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+/// Unit of a physical quantity
+///
+/// Quantities are always stored in the unit without any SI prefix.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+)]
+pub enum Unit {
+    /// Degrees Celsius (`°C`)
+    Celsius,
+    /// Relative humidity (`%RH`)
+    RelativeHumidity,
+    /// Percent (`%`)
+    Percent,
+    /// Parts per million (`ppm`), also accepts parts per billion (`ppb`)
+    Ppm,
+    /// Pascal (`Pa`), also accepts `bar`
+    Pascal,
+    /// Standard gravity (`g`)
+    StandardGravity,
+    /// Degrees per second (`dps` or `°/s`)
+    DegreesPerSecond,
+    /// Tesla (`T`), also accepts gauss (`G`)
+    Tesla,
+    /// Lux (`lx`)
+    Lux,
+    /// Meter (`m`)
+    Meter,
+    /// Volt (`V`)
+    Volt,
+    /// Ampere (`A`)
+    Ampere,
+    /// Watt (`W`)
+    Watt,
+    /// Hertz (`Hz`)
+    Hertz,
+    /// Second (`s`)
+    Second,
+    /// Ohm (`Ω`)
+    Ohm,
+    /// Farad (`F`)
+    Farad,
+}
+
+/// Symbols that are accepted as-is, with the factor to convert to the unit
+const SYMBOLS: &[(&str, Unit, f64)] = &[
+    ("°C", Unit::Celsius, 1.0),
+    ("degC", Unit::Celsius, 1.0),
+    ("%RH", Unit::RelativeHumidity, 1.0),
+    ("%", Unit::Percent, 1.0),
+    ("ppm", Unit::Ppm, 1.0),
+    ("ppb", Unit::Ppm, 1e-3),
+    ("°/s", Unit::DegreesPerSecond, 1.0),
+    ("G", Unit::Tesla, 1e-4),
+    ("mG", Unit::Tesla, 1e-7),
+    ("bar", Unit::Pascal, 1e5),
+    ("mbar", Unit::Pascal, 1e2),
+];
+
+/// Symbols that may have an SI prefix
+const PREFIXABLE_SYMBOLS: &[(&str, Unit)] = &[
+    ("Pa", Unit::Pascal),
+    ("g", Unit::StandardGravity),
+    ("dps", Unit::DegreesPerSecond),
+    ("T", Unit::Tesla),
+    ("lx", Unit::Lux),
+    ("m", Unit::Meter),
+    ("V", Unit::Volt),
+    ("A", Unit::Ampere),
+    ("W", Unit::Watt),
+    ("Hz", Unit::Hertz),
+    ("s", Unit::Second),
+    ("Ω", Unit::Ohm),
+    ("Ohm", Unit::Ohm),
+    ("F", Unit::Farad),
+];
+
+const PREFIXES: &[(&str, f64)] = &[
+    ("p", 1e-12),
+    ("n", 1e-9),
+    ("µ", 1e-6),
+    ("μ", 1e-6),
+    ("u", 1e-6),
+    ("m", 1e-3),
+    ("c", 1e-2),
+    ("h", 1e2),
+    ("k", 1e3),
+    ("M", 1e6),
+    ("G", 1e9),
+];
+
+impl Unit {
+    /// Symbol used when displaying quantities of this unit
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Celsius => "°C",
+            Unit::RelativeHumidity => "%RH",
+            Unit::Percent => "%",
+            Unit::Ppm => "ppm",
+            Unit::Pascal => "Pa",
+            Unit::StandardGravity => "g",
+            Unit::DegreesPerSecond => "dps",
+            Unit::Tesla => "T",
+            Unit::Lux => "lx",
+            Unit::Meter => "m",
+            Unit::Volt => "V",
+            Unit::Ampere => "A",
+            Unit::Watt => "W",
+            Unit::Hertz => "Hz",
+            Unit::Second => "s",
+            Unit::Ohm => "Ω",
+            Unit::Farad => "F",
+        }
+    }
+
+    fn is_prefixable(&self) -> bool {
+        PREFIXABLE_SYMBOLS.iter().any(|(_, unit)| unit == self)
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// Parse a unit symbol with an optional SI prefix, returning the unit and the factor to convert to it
+fn parse_symbol(s: &str) -> Option<(Unit, f64)> {
+    if let Some((_, unit, factor)) = SYMBOLS.iter().find(|(symbol, _, _)| *symbol == s) {
+        return Some((*unit, *factor));
+    }
+
+    if let Some((_, unit)) = PREFIXABLE_SYMBOLS.iter().find(|(symbol, _)| *symbol == s) {
+        return Some((*unit, 1.0));
+    }
+
+    PREFIXES.iter().find_map(|(prefix, factor)| {
+        let symbol = s.strip_prefix(prefix)?;
+        let (_, unit) = PREFIXABLE_SYMBOLS.iter().find(|(s, _)| *s == symbol)?;
+        Some((*unit, *factor))
+    })
+}
+
+/// A physical quantity, like `3.3 V` or `-40 °C`
+#[derive(Copy, Clone, PartialEq, PartialOrd)]
+pub struct Quantity {
+    /// Value in `unit`, without any SI prefix
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Self { value, unit }
+    }
+
+    /// Check that this quantity has the given unit
+    pub fn expect_unit(&self, unit: Unit) -> anyhow::Result<()> {
+        if self.unit != unit {
+            bail!("Expected a quantity in {unit}, found {self}");
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix('±').unwrap_or(s).trim_start();
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E')))
+            .with_context(|| format!("Quantity is missing a unit: {s:?}"))?;
+        let (value, symbol) = s.split_at(split);
+        let value: f64 = value
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid number in quantity: {s:?}"))?;
+        let (unit, factor) = parse_symbol(symbol.trim())
+            .with_context(|| format!("Unknown unit in quantity: {s:?}"))?;
+
+        if !value.is_finite() {
+            bail!("Quantity is not finite: {s:?}");
+        }
+
+        Ok(Quantity {
+            value: value * factor,
+            unit,
+        })
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const DISPLAY_PREFIXES: &[(&str, f64)] = &[
+            ("G", 1e9),
+            ("M", 1e6),
+            ("k", 1e3),
+            ("", 1.0),
+            ("m", 1e-3),
+            ("µ", 1e-6),
+            ("n", 1e-9),
+            ("p", 1e-12),
+        ];
+
+        let (value, prefix) = if self.unit.is_prefixable() && self.value != 0.0 {
+            let magnitude = self.value.abs();
+            DISPLAY_PREFIXES
+                .iter()
+                .find(|(_, factor)| magnitude >= *factor * 0.999_999)
+                .map(|(prefix, factor)| (self.value / factor, *prefix))
+                .unwrap_or((self.value, ""))
+        } else {
+            (self.value, "")
+        };
+
+        // Round away floating point noise from unit conversions
+        let value: f64 = format!("{value:.8e}")
+            .parse()
+            .map_err(|_| std::fmt::Error)?;
+        if value != 0.0 && value.abs() < 1e-3 {
+            write!(f, "{value:e} {prefix}{}", self.unit)
+        } else {
+            write!(f, "{value} {prefix}{}", self.unit)
+        }
+    }
+}
+
+impl Debug for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Serialize for Quantity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl JsonSchema for Quantity {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Quantity".into()
+    }
+
+    fn json_schema(_gen: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        json_schema!({
+            "description": "Number followed by a unit symbol with optional SI prefix, e.g. `3.3 V` or `-40 °C`",
+            "type": "string",
+            "pattern": r"^(± ?)?[-+]?[0-9]*\.?[0-9]+([eE][-+]?[0-9]+)? ?\S+$",
+        })
+    }
+}
+
+/// Range of a physical quantity, written as `[min, max]`
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "[Quantity; 2]", into = "[Quantity; 2]")]
+pub struct QuantityRange {
+    pub min: Quantity,
+    pub max: Quantity,
+}
+
+impl QuantityRange {
+    pub fn unit(&self) -> Unit {
+        self.min.unit
+    }
+
+    /// Check whether `value` lies within this range
+    pub fn contains(&self, value: &Quantity) -> bool {
+        value.unit == self.unit() && self.min.value <= value.value && value.value <= self.max.value
+    }
+
+    /// Check whether `other` lies completely within this range
+    pub fn covers(&self, other: &QuantityRange) -> bool {
+        self.contains(&other.min) && self.contains(&other.max)
+    }
+}
+
+impl TryFrom<[Quantity; 2]> for QuantityRange {
+    type Error = anyhow::Error;
+
+    fn try_from([min, max]: [Quantity; 2]) -> Result<Self, Self::Error> {
+        if min.unit != max.unit {
+            bail!("Range bounds have different units: {min} and {max}");
+        }
+        if min.value > max.value {
+            bail!("Range minimum {min} is larger than its maximum {max}");
+        }
+        Ok(Self { min, max })
+    }
+}
+
+impl From<QuantityRange> for [Quantity; 2] {
+    fn from(value: QuantityRange) -> Self {
+        [value.min, value.max]
+    }
+}
+
+impl Display for QuantityRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} to {}", self.min, self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quantity(s: &str) -> Quantity {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_tolerance_with_space() {
+        assert_eq!(quantity("± 0.5 °C"), Quantity::new(0.5, Unit::Celsius));
+        assert_eq!(quantity("±0.5 °C"), Quantity::new(0.5, Unit::Celsius));
+    }
+
+    #[test]
+    fn parses_pico_prefix() {
+        assert_eq!(quantity("10 pF"), Quantity::new(10e-12, Unit::Farad));
+        assert_eq!(quantity("250 pA"), Quantity::new(250e-12, Unit::Ampere));
+        assert_eq!(quantity("1 ppm"), Quantity::new(1.0, Unit::Ppm));
+    }
+
+    #[test]
+    fn displays_small_values() {
+        assert_eq!(quantity("10 pF").to_string(), "10 pF");
+        assert_eq!(quantity("0.5 nA").to_string(), "500 pA");
+        assert_eq!(quantity("0.001 pA").to_string(), "1e-15 A");
+        assert_eq!(quantity("0.0000000001 °C").to_string(), "1e-10 °C");
+        assert_eq!(quantity("3.3 V").to_string(), "3.3 V");
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for s in [
+            "10 pF",
+            "0.001 pA",
+            "1e-10 °C",
+            "± 0.5 °C",
+            "300 mbar",
+            "0.1 ppb",
+        ] {
+            let parsed = quantity(s);
+            assert_eq!(quantity(&parsed.to_string()), parsed, "{s}");
+        }
+    }
+}
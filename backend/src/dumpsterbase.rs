@@ -1,10 +1,11 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use spdx::ParseMode;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, JsonSchema)]
 pub enum DependencyKind {
     Normal,
     Build,
@@ -21,11 +22,25 @@ impl From<db_dump::dependencies::DependencyKind> for DependencyKind {
     }
 }
 
+/// Reason why no version could be found for a dependency requirement
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum UnresolvedReason {
+    /// The dump contains no versions of the dependency at all
+    UnknownCrate,
+    /// None of the published versions satisfy the requirement
+    NoMatchingVersion,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Dependency {
     pub name: String,
     pub req: semver::VersionReq,
     pub newest_version: Option<semver::Version>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub unresolved: Option<UnresolvedReason>,
+    /// Declared MSRV of the newest matching version
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rust_version: Option<semver::Version>,
     pub optional: bool,
     pub kind: DependencyKind,
     pub default_features: bool,
@@ -46,7 +61,11 @@ pub struct Version {
     pub crate_size: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub rust_version: Option<semver::Version>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub edition: Option<u16>,
     pub dependencies: Vec<Dependency>,
+    /// When this version was published
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,9 +110,8 @@ impl CrateDb {
         let dep_versions: BTreeMap<_, Vec<_>> = dependenants
             .iter()
             .map(|d| {
-                let mut versions: Vec<_> = d.versions.iter().map(|v| v.version.clone()).collect();
-                versions.sort_unstable();
-                versions.reverse();
+                let mut versions: Vec<_> = d.versions.iter().collect();
+                versions.sort_unstable_by(|a, b| b.version.cmp(&a.version));
                 (d.name.clone(), versions)
             })
             .collect();
@@ -103,10 +121,18 @@ impl CrateDb {
             .flat_map(|c| &mut c.versions)
             .flat_map(|v| &mut v.dependencies)
         {
-            dependency.newest_version = dep_versions
-                .get(&dependency.name)
-                .and_then(|versions| versions.iter().find(|v| dependency.req.matches(v)))
-                .cloned();
+            let versions = dep_versions.get(&dependency.name);
+            let newest = versions
+                .and_then(|versions| versions.iter().find(|v| dependency.req.matches(&v.version)));
+            dependency.newest_version = newest.map(|v| v.version.clone());
+            dependency.rust_version = newest.and_then(|v| v.rust_version.clone());
+            dependency.unresolved = match (versions, &dependency.newest_version) {
+                (_, Some(_)) => None,
+                (Some(versions), None) if !versions.is_empty() => {
+                    Some(UnresolvedReason::NoMatchingVersion)
+                }
+                _ => Some(UnresolvedReason::UnknownCrate),
+            };
         }
 
         Ok(CrateDb {
@@ -202,6 +228,8 @@ impl CrateDb {
                                     name: crate_name.clone(),
                                     req: dep.req.clone(),
                                     newest_version: None,
+                                    unresolved: None,
+                                    rust_version: None,
                                     optional: dep.optional,
                                     kind: dep.kind.into(),
                                     default_features: dep.default_features,
@@ -219,7 +247,9 @@ impl CrateDb {
                             license: row.license.clone(),
                             crate_size: row.crate_size,
                             rust_version: row.rust_version.clone(),
+                            edition: row.edition,
                             dependencies,
+                            created_at: row.created_at,
                         }
                     })
                     .collect();
@@ -0,0 +1,129 @@
+//! Checking whether chips can share an I2C bus without address conflicts
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::FullCrate;
+
+/// Addresses for a set of chips on one I2C bus
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BusPlan {
+    /// Can every device get an address of its own?
+    pub fits: bool,
+    /// Address of each device, in the order they were given
+    ///
+    /// Empty if the devices can't all get a different address.
+    pub assignment: Vec<(String, u8)>,
+    /// Pairs of devices that can use some of the same addresses
+    pub overlaps: Vec<AddressOverlap>,
+    /// Drivers without an I2C interface or without known addresses
+    pub unknown: Vec<String>,
+}
+
+/// Addresses two devices on a bus have in common
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AddressOverlap {
+    pub first: String,
+    pub second: String,
+    pub addresses: Vec<u8>,
+}
+
+/// Find an address for every device, each crate standing for one device on the bus
+///
+/// A crate can be given multiple times for multiple devices of the same kind.
+pub fn plan_bus(devices: &[&FullCrate]) -> BusPlan {
+    let mut unknown = vec![];
+    let mut options = vec![];
+    for krate in devices {
+        match &krate.interfaces.i2c {
+            Some(i2c) if !i2c.addrs.is_empty() => options.push((&krate.name, &i2c.addrs)),
+            _ => unknown.push(krate.name.clone()),
+        }
+    }
+
+    let mut overlaps = vec![];
+    for (i, (first, first_addrs)) in options.iter().enumerate() {
+        for (second, second_addrs) in &options[i + 1..] {
+            let addresses: Vec<u8> = first_addrs
+                .iter()
+                .filter(|addr| second_addrs.contains(addr))
+                .copied()
+                .collect();
+            if !addresses.is_empty() {
+                overlaps.push(AddressOverlap {
+                    first: (*first).clone(),
+                    second: (*second).clone(),
+                    addresses,
+                });
+            }
+        }
+    }
+
+    let addrs: Vec<&[u8]> = options.iter().map(|(_, addrs)| addrs.as_slice()).collect();
+    let assigned = assign(&addrs);
+    let fits = assigned.is_some() && unknown.is_empty();
+    let assignment = assigned
+        .map(|assigned| {
+            options
+                .iter()
+                .zip(assigned)
+                .map(|((name, _), addr)| ((*name).clone(), addr))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    BusPlan {
+        fits,
+        assignment,
+        overlaps,
+        unknown,
+    }
+}
+
+/// Pick a different address for each device, preferring the first listed ones
+///
+/// This is a bipartite matching between devices and addresses, found with augmenting paths.
+fn assign(options: &[&[u8]]) -> Option<Vec<u8>> {
+    // Device using each of the 128 addresses
+    let mut owner: [Option<usize>; 128] = [None; 128];
+
+    fn augment(
+        device: usize,
+        options: &[&[u8]],
+        owner: &mut [Option<usize>; 128],
+        visited: &mut [bool; 128],
+    ) -> bool {
+        for &addr in options[device] {
+            // Manifests only allow 7-bit addresses, anything else can't be used
+            let slot = usize::from(addr);
+            if slot >= owner.len() || visited[slot] {
+                continue;
+            }
+            visited[slot] = true;
+
+            let free = match owner[slot] {
+                None => true,
+                Some(other) => augment(other, options, owner, visited),
+            };
+            if free {
+                owner[slot] = Some(device);
+                return true;
+            }
+        }
+        false
+    }
+
+    for device in 0..options.len() {
+        if !augment(device, options, &mut owner, &mut [false; 128]) {
+            return None;
+        }
+    }
+
+    let mut assigned = vec![0; options.len()];
+    for (addr, device) in owner.iter().enumerate() {
+        if let Some(device) = device {
+            assigned[*device] = addr as u8;
+        }
+    }
+    Some(assigned)
+}
@@ -0,0 +1,442 @@
+//! Reading the official KiCad symbol and footprint libraries, and linking symbols back to drivers
+//!
+//! Symbols are referenced as `Library:Symbol` like in KiCad itself, e.g. `Sensor_Energy:INA219AxD`.
+//! Footprints are referenced the same way, e.g. `Package_SO:SOIC-8_3.9x4.9mm_P1.27mm`.
+//! Driver descriptions from before this was settled may use `Library::Symbol`, which is accepted
+//! as well.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::FullCrate;
+
+/// Symbol from the KiCad symbol libraries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct KicadSymbol {
+    /// Reference as `Library:Symbol`
+    pub name: String,
+    /// Default footprint of the symbol
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub footprint: Option<String>,
+    /// Is the default footprint part of the footprint libraries?
+    ///
+    /// Only known if the footprint libraries were read.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub footprint_exists: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub datasheet: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub description: Option<String>,
+}
+
+/// Result of checking the KiCad symbols of a driver against the libraries
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct KicadInfo {
+    /// Symbols of the driver that exist in the libraries
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub symbols: Vec<KicadSymbol>,
+    /// Symbols of the driver that do not exist in the libraries
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub unknown: Vec<String>,
+    /// Symbols matching the part numbers of a driver without any symbols
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub suggestions: Vec<String>,
+}
+
+/// Symbols and footprints of a local checkout of the KiCad libraries
+#[derive(Debug, Clone, Default)]
+pub struct KicadLibrary {
+    symbols: BTreeMap<String, KicadSymbol>,
+    /// Footprints as `Library:Footprint`, if the footprint libraries were read
+    footprints: Option<BTreeSet<String>>,
+}
+
+impl KicadLibrary {
+    /// Read all symbol libraries in `dir`, failing if there are none
+    ///
+    /// Libraries are either single `*.kicad_sym` files or `*.kicad_symdir` directories with a
+    /// `*.kicad_sym` file per symbol.
+    pub fn from_symbol_dir(dir: &Path) -> anyhow::Result<Self> {
+        let mut symbols = BTreeMap::new();
+        let mut libraries = 0;
+
+        for entry in std::fs::read_dir(dir)
+            .with_context(|| format!("Could not read KiCad symbol directory {dir:?}"))?
+        {
+            let path = entry?.path();
+            let files = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("kicad_sym") => vec![path.clone()],
+                Some("kicad_symdir") if path.is_dir() => {
+                    let mut files = vec![];
+                    for file in std::fs::read_dir(&path)? {
+                        let file = file?.path();
+                        if file.extension().is_some_and(|ext| ext == "kicad_sym") {
+                            files.push(file);
+                        }
+                    }
+                    files
+                }
+                _ => continue,
+            };
+            let Some(library) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            libraries += 1;
+            let contents = files
+                .into_iter()
+                .map(|file| Ok((std::fs::read_to_string(&file)?, file)))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let parsed = parse_symbol_lib(library, &contents)?;
+            symbols.extend(
+                parsed
+                    .into_iter()
+                    .map(|symbol| (symbol.name.clone(), symbol)),
+            );
+        }
+
+        if libraries == 0 {
+            bail!("No *.kicad_sym or *.kicad_symdir symbol libraries found in {dir:?}");
+        }
+
+        Ok(Self {
+            symbols,
+            footprints: None,
+        })
+    }
+
+    /// Read the names of all footprints in the `*.pretty` directories in `dir`
+    pub fn read_footprint_dir(&mut self, dir: &Path) -> anyhow::Result<()> {
+        let mut footprints = BTreeSet::new();
+
+        for entry in std::fs::read_dir(dir)
+            .with_context(|| format!("Could not read KiCad footprint directory {dir:?}"))?
+        {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "pretty") {
+                continue;
+            }
+            let Some(library) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            for footprint in std::fs::read_dir(&path)? {
+                let footprint = footprint?.path();
+                if footprint.extension().is_none_or(|ext| ext != "kicad_mod") {
+                    continue;
+                }
+                if let Some(name) = footprint.file_stem().and_then(|stem| stem.to_str()) {
+                    footprints.insert(format!("{library}:{name}"));
+                }
+            }
+        }
+
+        for symbol in self.symbols.values_mut() {
+            symbol.footprint_exists = symbol
+                .footprint
+                .as_ref()
+                .map(|footprint| footprints.contains(footprint));
+        }
+        self.footprints = Some(footprints);
+        Ok(())
+    }
+
+    pub fn symbol_count(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn footprint_count(&self) -> Option<usize> {
+        self.footprints.as_ref().map(BTreeSet::len)
+    }
+
+    /// Look up a symbol, accepting `Library::Symbol` as well
+    pub fn symbol(&self, name: &str) -> Option<&KicadSymbol> {
+        self.symbols.get(&normalize_name(name))
+    }
+
+    /// Check the symbols of a driver, suggesting symbols by part number if it has none
+    pub fn check(&self, symbols: &[String], part_numbers: &[&str]) -> KicadInfo {
+        let mut info = KicadInfo::default();
+
+        for name in symbols {
+            match self.symbol(name) {
+                Some(symbol) => info.symbols.push(symbol.clone()),
+                None => info.unknown.push(name.clone()),
+            }
+        }
+
+        if symbols.is_empty() {
+            info.suggestions = self.suggest(part_numbers);
+        }
+
+        info
+    }
+
+    /// Find symbols whose name matches one of the part numbers
+    ///
+    /// KiCad uses a lowercase `x` in symbol names as a placeholder for any character,
+    /// e.g. `INA219AxD` covers `INA219AID`.
+    pub fn suggest(&self, part_numbers: &[&str]) -> Vec<String> {
+        self.symbols
+            .values()
+            .filter(|symbol| {
+                let (_, name) = symbol.name.split_once(':').unwrap_or_default();
+                part_numbers
+                    .iter()
+                    .any(|part| matches_part_number(name, part))
+            })
+            .map(|symbol| symbol.name.clone())
+            .collect()
+    }
+}
+
+/// Row of the symbol fields table linking KiCad symbols to Rust drivers
+///
+/// The column names match the fields added to the symbols.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DriverFields {
+    #[serde(rename = "Symbol")]
+    pub symbol: String,
+    #[serde(rename = "Rust_Driver")]
+    pub rust_driver: String,
+    #[serde(rename = "Rust_Driver_Version")]
+    pub rust_driver_version: semver::Version,
+    #[serde(rename = "Rust_Driver_Docs")]
+    pub rust_driver_docs: String,
+}
+
+/// Map the KiCad symbols of all crates to the crates supporting them
+///
+/// If the symbols were checked against the KiCad libraries, unknown symbols are left out.
+pub fn driver_fields(crates: &[FullCrate]) -> Vec<DriverFields> {
+    let mut rows = crates
+        .iter()
+        .flat_map(|krate| {
+            let symbols = match &krate.kicad {
+                Some(info) => info
+                    .symbols
+                    .iter()
+                    .map(|symbol| symbol.name.clone())
+                    .collect(),
+                None => krate
+                    .chip_meta
+                    .kicad_symbol
+                    .iter()
+                    .map(|name| normalize_name(name))
+                    .collect::<Vec<_>>(),
+            };
+
+            symbols.into_iter().map(|symbol| DriverFields {
+                symbol,
+                rust_driver: krate.name.clone(),
+                rust_driver_version: krate.version.clone(),
+                rust_driver_docs: krate
+                    .documentation
+                    .clone()
+                    .unwrap_or_else(|| format!("https://docs.rs/{}/{}", krate.name, krate.version)),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    rows.sort_by(|a, b| (&a.symbol, &a.rust_driver).cmp(&(&b.symbol, &b.rust_driver)));
+    rows
+}
+
+/// Write the symbol fields table of all crates as CSV
+pub fn write_driver_fields(crates: &[FullCrate], path: &Path) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    for row in driver_fields(crates) {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Turn `Library::Symbol` into the `Library:Symbol` used by KiCad
+fn normalize_name(name: &str) -> String {
+    name.replacen("::", ":", 1)
+}
+
+/// Check whether the symbol name is the start of the part number
+fn matches_part_number(symbol: &str, part: &str) -> bool {
+    // Short names like `R` would match far too much
+    if symbol.len() < 4 || symbol.len() > part.len() {
+        return false;
+    }
+
+    symbol
+        .chars()
+        .zip(part.chars())
+        .all(|(s, p)| s == 'x' || s.eq_ignore_ascii_case(&p))
+}
+
+/// Node of an S-expression as used in KiCad files
+#[derive(Debug, Clone, PartialEq)]
+enum Sexpr {
+    Atom(String),
+    List(Vec<Sexpr>),
+}
+
+impl Sexpr {
+    fn as_atom(&self) -> Option<&str> {
+        match self {
+            Sexpr::Atom(atom) => Some(atom),
+            Sexpr::List(_) => None,
+        }
+    }
+
+    /// Get the items of a list starting with `keyword`
+    fn as_list(&self, keyword: &str) -> Option<&[Sexpr]> {
+        match self {
+            Sexpr::List(items) if items.first()?.as_atom()? == keyword => Some(&items[1..]),
+            _ => None,
+        }
+    }
+}
+
+fn parse_sexpr(s: &str) -> anyhow::Result<Sexpr> {
+    let mut stack: Vec<Vec<Sexpr>> = vec![vec![]];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => stack.push(vec![]),
+            ')' => {
+                let list = stack.pop().context("Unbalanced closing parenthesis")?;
+                stack
+                    .last_mut()
+                    .context("Unbalanced closing parenthesis")?
+                    .push(Sexpr::List(list));
+            }
+            '"' => {
+                let mut atom = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => atom.push('\n'),
+                            Some(c) => atom.push(c),
+                            None => bail!("Unterminated string"),
+                        },
+                        Some(c) => atom.push(c),
+                        None => bail!("Unterminated string"),
+                    }
+                }
+                stack
+                    .last_mut()
+                    .context("Unbalanced closing parenthesis")?
+                    .push(Sexpr::Atom(atom));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut atom = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    atom.push(c);
+                    chars.next();
+                }
+                stack
+                    .last_mut()
+                    .context("Unbalanced closing parenthesis")?
+                    .push(Sexpr::Atom(atom));
+            }
+        }
+    }
+
+    let mut top = stack.pop().context("Unbalanced closing parenthesis")?;
+    if !stack.is_empty() {
+        bail!("Unbalanced opening parenthesis");
+    }
+    if top.len() != 1 {
+        bail!("Expected a single expression, found {}", top.len());
+    }
+    Ok(top.remove(0))
+}
+
+/// Parse the contents of the `.kicad_sym` files of a library, together with their paths
+///
+/// Symbols may extend symbols from other files of the same library.
+fn parse_symbol_lib(
+    library: &str,
+    files: &[(String, PathBuf)],
+) -> anyhow::Result<Vec<KicadSymbol>> {
+    struct RawSymbol {
+        extends: Option<String>,
+        properties: BTreeMap<String, String>,
+    }
+
+    let mut raw = BTreeMap::new();
+    for (content, path) in files {
+        let root = parse_sexpr(content)
+            .with_context(|| format!("Could not parse KiCad symbol library {path:?}"))?;
+        let items = root
+            .as_list("kicad_symbol_lib")
+            .with_context(|| format!("{path:?} is not a KiCad symbol library"))?;
+
+        for item in items {
+            let Some(symbol) = item.as_list("symbol") else {
+                continue;
+            };
+            let name = symbol
+                .first()
+                .and_then(Sexpr::as_atom)
+                .with_context(|| format!("Symbol without name in {path:?}"))?;
+
+            let mut extends = None;
+            let mut properties = BTreeMap::new();
+            for field in &symbol[1..] {
+                if let Some([parent, ..]) = field.as_list("extends") {
+                    extends = parent.as_atom().map(str::to_string);
+                }
+                if let Some([key, value, ..]) = field.as_list("property") {
+                    if let (Some(key), Some(value)) = (key.as_atom(), value.as_atom()) {
+                        properties.insert(key.to_string(), value.to_string());
+                    }
+                }
+            }
+
+            raw.insert(
+                name.to_string(),
+                RawSymbol {
+                    extends,
+                    properties,
+                },
+            );
+        }
+    }
+
+    // Derived symbols inherit the properties they don't set themselves
+    let property = |name: &str, key: &str| -> Option<String> {
+        let mut current = raw.get(name);
+        // Bounded to not loop forever on broken libraries
+        for _ in 0..8 {
+            let symbol = current?;
+            if let Some(value) = symbol
+                .properties
+                .get(key)
+                .filter(|v| !v.is_empty() && *v != "~")
+            {
+                return Some(value.clone());
+            }
+            current = raw.get(symbol.extends.as_deref()?);
+        }
+        None
+    };
+
+    Ok(raw
+        .keys()
+        .map(|name| KicadSymbol {
+            name: format!("{library}:{name}"),
+            footprint: property(name, "Footprint"),
+            footprint_exists: None,
+            datasheet: property(name, "Datasheet"),
+            description: property(name, "Description").or_else(|| property(name, "ki_description")),
+        })
+        .collect())
+}
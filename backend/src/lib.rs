@@ -1,11 +1,25 @@
-use crate::driver_db::{boards::DevBoard, Driver, Interfaces, Resource};
-use anyhow::Context;
+use crate::build_report::{Diagnostic, DiagnosticKind};
+use crate::driver_db::boards::{Board, BoardRegistry};
+use crate::driver_db::categories::CategoryNode;
+use crate::driver_db::connectors::Connection;
+use crate::driver_db::{Driver, Interfaces, Resource};
+use crate::dumpsterbase::{DependencyKind, UnresolvedReason};
+use crate::kicad::{KicadInfo, KicadLibrary};
+use crate::msrv::Msrv;
+use crate::query::Query;
+use crate::website_db::indexes::Indexes;
+use crate::website_db::search::SearchIndex;
+use crate::website_db::Interface;
 use chrono::{DateTime, Utc};
-use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use spdx::ParseMode;
-use std::borrow::Cow;
 use std::fmt::Display;
+use std::path::Path;
+
+pub mod build_report;
+
+pub mod diff;
 
 pub mod driver_db;
 
@@ -13,12 +27,92 @@ pub mod awesome_embedded_rust;
 
 pub mod dumpsterbase;
 
+pub mod i2c;
+
+pub mod kicad;
+
+pub mod msrv;
+
+pub mod query;
+
+pub mod sqlite;
+
+pub mod typescript;
+
 pub mod website_db;
 
+/// Crate database as written to `full-crate-db.json` by `read-driver-db`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FullCrateDb {
+    pub crates: Vec<FullCrate>,
+    pub indexes: Indexes,
+    /// Full-text search index over names, descriptions, part numbers and categories
+    pub search: SearchIndex,
+    /// Tree of all categories, with the number of crates in each
+    pub categories: Vec<CategoryNode>,
+    /// All development boards, sorted by id
+    pub boards: Vec<Board>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FullCrateDb {
+    /// Read a crate database written by `read-driver-db`
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Position and description of the crate called `name`
+    pub fn get(&self, name: &str) -> Option<(usize, &FullCrate)> {
+        self.crates
+            .iter()
+            .enumerate()
+            .find(|(_, krate)| krate.name == name)
+    }
+
+    /// Start a query filtering and ranking the crates
+    pub fn query(&self) -> Query<'_> {
+        Query::new(self)
+    }
+}
+
+/// Where a driver is listed
+#[derive(
+    Debug, Copy, Clone, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, JsonSchema,
+)]
+pub enum Provenance {
+    /// Described in `driver-db`
+    Curated,
+    /// Only listed in the legacy list `aer.csv`, with little more than its interfaces
+    Legacy,
+}
+
+/// Description of a driver from `driver-db` or the legacy list, before adding the crates.io data
+#[derive(Debug, Clone)]
+pub struct Listing {
+    pub driver: Driver,
+    pub provenance: Provenance,
+    /// Interfaces that aren't described in [`Driver::interfaces`]
+    pub other_interfaces: Vec<Interface>,
+}
+
+impl From<Driver> for Listing {
+    fn from(driver: Driver) -> Self {
+        Self {
+            driver,
+            provenance: Provenance::Curated,
+            other_interfaces: vec![],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FullCrate {
     pub name: String,
     pub version: semver::Version,
+    /// When `version` was published
+    pub released_at: DateTime<Utc>,
     pub downloads: u64,
     pub this_version_downloads: u64,
     pub description: String,
@@ -30,31 +124,56 @@ pub struct FullCrate {
     pub repository: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub provenance: Provenance,
     #[serde(flatten)]
     pub chip_meta: driver_db::Meta,
+    /// Ids of the development boards housing a supported chip
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub dev_boards: Vec<String>,
+    /// Connectors of the development boards
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
-    pub dev_boards: Vec<DevBoard>,
+    pub connectors: Vec<Connection>,
     #[serde(skip_serializing_if = "Interfaces::is_empty", default)]
     pub interfaces: Interfaces,
+    /// Interfaces without a description in `interfaces`, like UART, or any interface of a legacy driver
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub other_interfaces: Vec<Interface>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub resources: Vec<Resource>,
     pub license: String,
     pub crate_size: Option<u64>,
+    /// Declared minimum supported Rust version
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub rust_version: Option<semver::Version>,
+    /// Declared or estimated minimum supported Rust version
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub msrv: Option<Msrv>,
     pub dependencies: Vec<ShortDependency>,
+    /// KiCad symbols checked against the KiCad libraries, if they were available
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub kicad: Option<KicadInfo>,
 }
 
 impl FullCrate {
-    pub fn new(driver_db: Driver, mut krate: dumpsterbase::Crate) -> anyhow::Result<Self> {
-        let version = krate
-            .versions
-            .pop()
-            .with_context(|| format!("No versions found for crate {:?}", krate.name))?;
+    pub fn new(listing: Listing, mut krate: dumpsterbase::Crate) -> Result<Self, Diagnostic> {
+        let version = krate.versions.pop().ok_or_else(|| {
+            Diagnostic::new(
+                DiagnosticKind::NoVersions,
+                &krate.name,
+                "No versions found for crate",
+            )
+        })?;
+        let msrv = Msrv::estimate(&version);
+        let Listing {
+            driver: driver_db,
+            provenance,
+            other_interfaces,
+        } = listing;
 
         Ok(Self {
             name: krate.name,
             version: version.version,
+            released_at: version.created_at,
             downloads: krate.downloads,
             this_version_downloads: version.downloads,
             description: krate.description,
@@ -63,21 +182,135 @@ impl FullCrate {
             repository: krate.repository,
             created_at: krate.created_at,
             updated_at: krate.updated_at,
+            provenance,
             chip_meta: driver_db.meta,
             dev_boards: driver_db.dev_boards,
+            connectors: vec![],
             interfaces: driver_db.interfaces,
+            other_interfaces,
             resources: driver_db.resources,
             license: version.license,
             crate_size: version.crate_size,
             rust_version: version.rust_version,
+            msrv,
             dependencies: version
                 .dependencies
                 .into_iter()
-                .map(ShortDependency::try_from)
-                .collect::<Result<_, _>>()?,
+                .map(ShortDependency::from)
+                .collect(),
+            kicad: None,
         })
     }
 
+    /// Check the KiCad symbols against the KiCad libraries
+    ///
+    /// Returns warnings about unknown symbols and footprints, and suggestions for drivers without symbols.
+    pub fn check_kicad(&mut self, library: &KicadLibrary) -> Vec<Diagnostic> {
+        let part_numbers = self
+            .chip_meta
+            .part_numbers
+            .iter()
+            .map(|part| part.number.as_str())
+            .chain(self.chip_meta.names.iter().map(String::as_str))
+            .collect::<Vec<_>>();
+        let info = library.check(&self.chip_meta.kicad_symbol, &part_numbers);
+
+        let mut diagnostics = vec![];
+        for name in &info.unknown {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::UnknownKicadSymbol,
+                &self.name,
+                format!("Symbol {name} does not exist"),
+            ));
+        }
+        for symbol in &info.symbols {
+            if let (Some(footprint), Some(false)) = (&symbol.footprint, symbol.footprint_exists) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::UnknownKicadFootprint,
+                    &self.name,
+                    format!(
+                        "Footprint {footprint} of symbol {} does not exist",
+                        symbol.name
+                    ),
+                ));
+            }
+        }
+        if !info.suggestions.is_empty() {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::SuggestedKicadSymbol,
+                &self.name,
+                format!("Matching symbols: {}", info.suggestions.join(", ")),
+            ));
+        }
+
+        self.kicad = Some(info);
+        diagnostics
+    }
+
+    /// Add the boards listing this crate for one of their chips and their connectors, and check that all boards exist
+    pub fn link_boards(&mut self, registry: &BoardRegistry) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        self.dev_boards.retain(|id| {
+            let known = registry.get(id).is_some();
+            if !known {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::UnknownBoard,
+                    &self.name,
+                    format!("Board {id} is not in the board registry"),
+                ));
+            }
+            known
+        });
+
+        for id in registry.boards_for_driver(&self.name) {
+            if !self.dev_boards.iter().any(|board| board == id) {
+                self.dev_boards.push(id.to_string());
+            }
+        }
+        self.dev_boards.sort();
+
+        self.connectors = self
+            .dev_boards
+            .iter()
+            .filter_map(|id| registry.get(id))
+            .flat_map(|board| board.connectors.iter().copied())
+            .collect();
+        self.connectors.sort();
+        self.connectors.dedup();
+
+        diagnostics
+    }
+
+    /// Does this crate have KiCad symbols, that exist in the libraries if they were checked?
+    pub fn has_kicad(&self) -> bool {
+        match &self.kicad {
+            Some(info) => !info.symbols.is_empty(),
+            None => !self.chip_meta.kicad_symbol.is_empty(),
+        }
+    }
+
+    /// All interfaces of the chips, described or not
+    pub fn all_interfaces(&self) -> Vec<Interface> {
+        let mut interfaces = vec![];
+        if self.interfaces.i2c.is_some() {
+            interfaces.push(Interface::I2C);
+        }
+        if self.interfaces.spi.is_some() {
+            interfaces.push(Interface::SPI);
+        }
+        interfaces.extend(&self.other_interfaces);
+        interfaces.sort();
+        interfaces.dedup();
+        interfaces
+    }
+
+    /// Dependencies for which no matching version was found
+    pub fn unresolved_dependencies(&self) -> impl Iterator<Item = &ShortDependency> {
+        self.dependencies
+            .iter()
+            .filter(|dep| dep.unresolved.is_some())
+    }
+
     pub fn licenses(&self) -> Vec<String> {
         match spdx::Expression::parse_mode(&self.license, ParseMode::LAX) {
             Ok(expr) => expr
@@ -89,65 +322,84 @@ impl FullCrate {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// Dependency of the newest version of a crate
+///
+/// Dependencies for which no published version matches the requirement are kept,
+/// with the reason why they could not be resolved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ShortDependency {
     pub name: String,
-    pub version: semver::Version,
+    /// Version requirement as published
+    #[schemars(with = "String")]
+    pub req: semver::VersionReq,
+    /// Newest published version matching the requirement
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub version: Option<semver::Version>,
+    /// Why no version matching the requirement was found
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub unresolved: Option<UnresolvedReason>,
+    /// Whether this is a normal, build or dev dependency
+    pub kind: DependencyKind,
+    /// Is this dependency only enabled by a feature?
+    pub optional: bool,
+    /// Are the default features of this dependency enabled?
+    pub default_features: bool,
+    /// Target platform this dependency is restricted to (if any)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub target: Option<String>,
 }
 
-impl TryFrom<dumpsterbase::Dependency> for ShortDependency {
-    type Error = anyhow::Error;
-
-    fn try_from(value: dumpsterbase::Dependency) -> Result<Self, Self::Error> {
-        Ok(Self {
-            name: value.name.clone(),
-            version: value.newest_version.with_context(|| {
-                format!(
-                    "No matching version found for crate {:?} with requirement {:?}",
-                    value.name, value.req,
-                )
-            })?,
-        })
+impl ShortDependency {
+    /// Is this dependency always pulled in when using the crate?
+    pub fn is_required(&self) -> bool {
+        self.kind == DependencyKind::Normal && !self.optional
     }
-}
 
-impl Display for ShortDependency {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}@{}", self.name, self.version)
+    /// Is this dependency pulled in when using the crate with some features enabled?
+    pub fn is_optional(&self) -> bool {
+        self.kind == DependencyKind::Normal && self.optional
     }
-}
 
-impl serde::Serialize for ShortDependency {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        serializer.collect_str(&self)
-    }
-}
+    /// Range of semver-compatible versions this dependency falls in
+    ///
+    /// This is the major version, or `0.minor` and `0.0.patch` for pre-1.0 versions,
+    /// e.g. `embedded-hal@1` or `embedded-hal@0.2`.
+    /// Unresolved dependencies use the first comparator of their requirement.
+    pub fn compatible_range(&self) -> String {
+        let (major, minor, patch) = match (&self.version, self.req.comparators.first()) {
+            (Some(version), _) => (version.major, Some(version.minor), Some(version.patch)),
+            (None, Some(comparator)) => (comparator.major, comparator.minor, comparator.patch),
+            (None, None) => return format!("{}@*", self.name),
+        };
 
-impl<'de> serde::Deserialize<'de> for ShortDependency {
-    fn deserialize<D>(deserializer: D) -> Result<ShortDependency, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-        let (name, version) = s
-            .split_once('@')
-            .ok_or_else(|| serde::de::Error::custom("Missing '@'"))?;
-        Ok(ShortDependency {
-            name: name.to_string(),
-            version: semver::Version::parse(version).map_err(serde::de::Error::custom)?,
-        })
+        match (major, minor, patch) {
+            (0, Some(0), Some(patch)) => format!("{}@0.0.{patch}", self.name),
+            (0, Some(minor), _) => format!("{}@0.{minor}", self.name),
+            (major, _, _) => format!("{}@{major}", self.name),
+        }
     }
 }
 
-impl JsonSchema for ShortDependency {
-    fn schema_name() -> Cow<'static, str> {
-        "ShortDependency".into()
+impl From<dumpsterbase::Dependency> for ShortDependency {
+    fn from(value: dumpsterbase::Dependency) -> Self {
+        Self {
+            name: value.name,
+            req: value.req,
+            version: value.newest_version,
+            unresolved: value.unresolved,
+            kind: value.kind,
+            optional: value.optional,
+            default_features: value.default_features,
+            target: value.target,
+        }
     }
+}
 
-    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
-        json_schema!({"type": "string", "pattern": r"^[^@]+@(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)(?:-((?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)(?:\.(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?(?:\+([0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?$"})
+impl Display for ShortDependency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{}@{}", self.name, version),
+            None => write!(f, "{}@{}", self.name, self.req),
+        }
     }
 }
@@ -0,0 +1,77 @@
+use crate::dumpsterbase::{self, DependencyKind};
+use schemars::JsonSchema;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// Minimum supported Rust version of a crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Msrv {
+    pub version: Version,
+    /// Where this version was derived from
+    pub source: MsrvSource,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum MsrvSource {
+    /// The crate declares a `rust-version`
+    Declared,
+    /// Oldest toolchain supporting the edition of the crate
+    Edition,
+    /// Highest `rust-version` declared by a non-dev dependency
+    Dependencies,
+}
+
+impl Msrv {
+    /// Determine the MSRV of a crate version
+    ///
+    /// A declared `rust-version` is used as-is, otherwise the MSRV is estimated from
+    /// the edition and the MSRVs of the dependencies.
+    pub fn estimate(version: &dumpsterbase::Version) -> Option<Self> {
+        if let Some(declared) = &version.rust_version {
+            return Some(Msrv {
+                version: declared.clone(),
+                source: MsrvSource::Declared,
+            });
+        }
+
+        let edition = version.edition.and_then(edition_msrv).map(|version| Msrv {
+            version,
+            source: MsrvSource::Edition,
+        });
+
+        let dependencies = version
+            .dependencies
+            .iter()
+            .filter(|dep| dep.kind != DependencyKind::Dev && !dep.optional)
+            .filter_map(|dep| dep.rust_version.clone())
+            .max()
+            .map(|version| Msrv {
+                version,
+                source: MsrvSource::Dependencies,
+            });
+
+        match (edition, dependencies) {
+            (Some(edition), Some(dependencies)) if dependencies.version > edition.version => {
+                Some(dependencies)
+            }
+            (Some(edition), _) => Some(edition),
+            (None, dependencies) => dependencies,
+        }
+    }
+
+    pub fn is_estimated(&self) -> bool {
+        self.source != MsrvSource::Declared
+    }
+}
+
+/// Oldest Rust version that supports an edition
+///
+/// The 2015 edition is supported by every Rust release, so it gives no information.
+pub fn edition_msrv(edition: u16) -> Option<Version> {
+    match edition {
+        2018 => Some(Version::new(1, 31, 0)),
+        2021 => Some(Version::new(1, 56, 0)),
+        2024 => Some(Version::new(1, 85, 0)),
+        _ => None,
+    }
+}
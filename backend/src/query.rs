@@ -0,0 +1,322 @@
+//! Filtering and ranking the crates of a [`FullCrateDb`] by intersecting its indexes
+//!
+//! ```no_run
+//! # fn main() -> anyhow::Result<()> {
+//! use drivers::website_db::Interface;
+//! use drivers::FullCrateDb;
+//!
+//! let db = FullCrateDb::read("full-crate-db.json".as_ref())?;
+//! let results = db
+//!     .query()
+//!     .category("Sensor::Temperature".parse()?)
+//!     .interface(Interface::I2C)
+//!     .dependency_range("embedded-hal@1")
+//!     .builds_on(&"1.75.0".parse()?)
+//!     .text("humidity")
+//!     .run();
+//! for found in results {
+//!     println!("{} {}", found.krate.name, found.krate.version);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeSet;
+use std::ops::{Bound, RangeBounds};
+
+use semver::Version;
+
+use crate::driver_db::categories::Category;
+use crate::driver_db::measurements::MeasuredQuantity;
+use crate::driver_db::packages::PackageFamily;
+use crate::driver_db::units::{Quantity, QuantityRange};
+use crate::website_db::indexes::Index;
+use crate::website_db::Interface;
+use crate::{FullCrate, FullCrateDb, Provenance};
+
+/// Condition a crate has to meet to be included in the results of a [`Query`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Listed in the category or one of its descendants
+    Category(Category),
+    Interface(Interface),
+    /// Curated or only listed in the legacy list
+    Provenance(Provenance),
+    /// One of the chips is available in this package
+    Package(PackageFamily),
+    /// Measures the quantity over at least `range`, with an absolute accuracy of at most `accuracy`
+    Measurement {
+        quantity: MeasuredQuantity,
+        range: Option<QuantityRange>,
+        accuracy: Option<Quantity>,
+    },
+    /// License that is part of the crate's license expression, e.g. `MIT` for `MIT OR Apache-2.0`
+    License(String),
+    /// Minimum supported Rust version in the range, crates without a known one are left out
+    Msrv(Bound<Version>, Bound<Version>),
+    /// Always enabled normal dependency, by crate name
+    Dependency(String),
+    /// Always enabled normal dependency, by semver-compatible range like `embedded-hal@1`
+    DependencyRange(String),
+    HasKicad,
+    HasDevBoard,
+}
+
+impl Filter {
+    /// Positions of the crates in `db` meeting this condition
+    fn matches(&self, db: &FullCrateDb) -> BTreeSet<usize> {
+        let indexes = &db.indexes;
+        match self {
+            Filter::Category(category) => entry(&indexes.category, category),
+            Filter::Interface(interface) => entry(&indexes.interfaces, interface),
+            Filter::Provenance(provenance) => entry(&indexes.provenance, provenance),
+            Filter::Package(family) => entry(&indexes.package, family),
+            Filter::Measurement {
+                quantity,
+                range,
+                accuracy,
+            } => {
+                let mut ids = entry(&indexes.measured_quantity, quantity);
+                ids.retain(|id| {
+                    db.crates[*id]
+                        .chip_meta
+                        .measurements
+                        .iter()
+                        .filter(|m| m.quantity == *quantity)
+                        .any(|m| m.meets(range.as_ref(), accuracy.as_ref()))
+                });
+                ids
+            }
+            Filter::License(license) => entry(&indexes.license, license),
+            Filter::Msrv(start, end) if is_empty_range(start, end) => BTreeSet::new(),
+            Filter::Msrv(start, end) => indexes
+                .rust_version
+                .0
+                .range((start.as_ref(), end.as_ref()))
+                .flat_map(|(_, crates)| crates)
+                .copied()
+                .collect(),
+            Filter::Dependency(name) => entry(&indexes.dependency_names, name),
+            Filter::DependencyRange(range) => entry(&indexes.dependencies, range),
+            Filter::HasKicad => indexes.has_kicad.clone(),
+            Filter::HasDevBoard => indexes.has_dev_board.clone(),
+        }
+    }
+}
+
+/// Does the range between `start` and `end` contain no values at all?
+///
+/// [`BTreeMap::range`](std::collections::BTreeMap::range) panics on those instead.
+fn is_empty_range<T: Ord>(start: &Bound<T>, end: &Bound<T>) -> bool {
+    match (start, end) {
+        (Bound::Included(start), Bound::Included(end)) => start > end,
+        (Bound::Included(start), Bound::Excluded(end))
+        | (Bound::Excluded(start), Bound::Included(end))
+        | (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+        (Bound::Unbounded, _) | (_, Bound::Unbounded) => false,
+    }
+}
+
+fn entry<T: Ord>(index: &Index<T>, key: &T) -> BTreeSet<usize> {
+    index.0.get(key).cloned().unwrap_or_default()
+}
+
+/// Crate found by a [`Query`]
+#[derive(Debug, Clone, Copy)]
+pub struct Found<'a> {
+    /// Position in [`FullCrateDb::crates`], as used by the indexes
+    pub id: usize,
+    pub krate: &'a FullCrate,
+    /// Relevance for the text query, if there was one
+    pub score: Option<u32>,
+}
+
+/// Builder for a query over a [`FullCrateDb`], created with [`FullCrateDb::query`]
+///
+/// Crates have to meet all filters. Results are ranked by relevance if a text query is given,
+/// otherwise by downloads.
+#[derive(Debug, Clone)]
+pub struct Query<'a> {
+    db: &'a FullCrateDb,
+    filters: Vec<Filter>,
+    text: Option<String>,
+    limit: Option<usize>,
+}
+
+impl<'a> Query<'a> {
+    pub fn new(db: &'a FullCrateDb) -> Self {
+        Self {
+            db,
+            filters: vec![],
+            text: None,
+            limit: None,
+        }
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Crates in `category` or one of its descendants
+    pub fn category(self, category: Category) -> Self {
+        self.filter(Filter::Category(category))
+    }
+
+    pub fn interface(self, interface: Interface) -> Self {
+        self.filter(Filter::Interface(interface))
+    }
+
+    pub fn provenance(self, provenance: Provenance) -> Self {
+        self.filter(Filter::Provenance(provenance))
+    }
+
+    pub fn package(self, family: PackageFamily) -> Self {
+        self.filter(Filter::Package(family))
+    }
+
+    /// Crates measuring `quantity`, over at least `range` and with an absolute accuracy of at most
+    /// `accuracy` if given
+    pub fn measures(
+        self,
+        quantity: MeasuredQuantity,
+        range: Option<QuantityRange>,
+        accuracy: Option<Quantity>,
+    ) -> Self {
+        self.filter(Filter::Measurement {
+            quantity,
+            range,
+            accuracy,
+        })
+    }
+
+    pub fn license(self, license: impl Into<String>) -> Self {
+        self.filter(Filter::License(license.into()))
+    }
+
+    /// Crates whose minimum supported Rust version is in `range`
+    pub fn msrv(self, range: impl RangeBounds<Version>) -> Self {
+        self.filter(Filter::Msrv(
+            range.start_bound().cloned(),
+            range.end_bound().cloned(),
+        ))
+    }
+
+    /// Crates that build on `toolchain`
+    pub fn builds_on(self, toolchain: &Version) -> Self {
+        self.msrv(..=toolchain.clone())
+    }
+
+    /// Crates always depending on the crate called `name`
+    pub fn dependency(self, name: impl Into<String>) -> Self {
+        self.filter(Filter::Dependency(name.into()))
+    }
+
+    /// Crates always depending on a semver-compatible range like `embedded-hal@1`
+    pub fn dependency_range(self, range: impl Into<String>) -> Self {
+        self.filter(Filter::DependencyRange(range.into()))
+    }
+
+    pub fn has_kicad(self) -> Self {
+        self.filter(Filter::HasKicad)
+    }
+
+    pub fn has_dev_board(self) -> Self {
+        self.filter(Filter::HasDevBoard)
+    }
+
+    /// Only keep crates matching the full-text search `text`, ranked by relevance
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Return at most `limit` results
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Positions of the crates meeting all filters, without ranking them
+    pub fn ids(&self) -> BTreeSet<usize> {
+        let mut filters = self.filters.iter();
+        let mut ids = match filters.next() {
+            Some(filter) => filter.matches(self.db),
+            None => (0..self.db.crates.len()).collect(),
+        };
+
+        for filter in filters {
+            if ids.is_empty() {
+                break;
+            }
+            let matches = filter.matches(self.db);
+            ids.retain(|id| matches.contains(id));
+        }
+        ids
+    }
+
+    /// Matching crates, most relevant first
+    pub fn run(&self) -> Vec<Found<'a>> {
+        let ids = self.ids();
+        let db = self.db;
+        let found = |id: usize, score| Found {
+            id,
+            krate: &db.crates[id],
+            score,
+        };
+
+        let mut results: Vec<_> = match &self.text {
+            Some(text) => db
+                .search
+                .search(text)
+                .into_iter()
+                .filter(|(id, _)| ids.contains(id))
+                .map(|(id, score)| found(id, Some(score)))
+                .collect(),
+            None => {
+                let mut results: Vec<_> = ids.into_iter().map(|id| found(id, None)).collect();
+                results.sort_by(|a, b| {
+                    b.krate
+                        .downloads
+                        .cmp(&a.krate.downloads)
+                        .then_with(|| a.krate.name.cmp(&b.krate.name))
+                });
+                results
+            }
+        };
+
+        if let Some(limit) = self.limit {
+            results.truncate(limit);
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_empty_ranges() {
+        let v = |minor| Version::new(1, minor, 0);
+
+        assert!(is_empty_range(
+            &Bound::Included(v(80)),
+            &Bound::Included(v(70))
+        ));
+        assert!(is_empty_range(
+            &Bound::Excluded(v(70)),
+            &Bound::Excluded(v(70))
+        ));
+        assert!(is_empty_range(
+            &Bound::Included(v(70)),
+            &Bound::Excluded(v(70))
+        ));
+        assert!(!is_empty_range(
+            &Bound::Included(v(70)),
+            &Bound::Included(v(70))
+        ));
+        assert!(!is_empty_range(&Bound::Excluded(v(70)), &Bound::Unbounded));
+        assert!(!is_empty_range(&Bound::Unbounded, &Bound::Excluded(v(70))));
+    }
+}
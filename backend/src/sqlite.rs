@@ -0,0 +1,407 @@
+//! Export of the crate database as SQLite, for ad-hoc queries and importing into other tools
+//!
+//! Crates are identified by their position in the crate list, like in [`Indexes`].
+//! Enums are stored with the names used in the JSON output, e.g. `StemmaQt`.
+
+use std::path::Path;
+
+use anyhow::Context;
+use rusqlite::{params, Connection, Transaction};
+use serde::Serialize;
+
+use crate::driver_db::boards::BoardRegistry;
+use crate::driver_db::categories::Category;
+use crate::website_db::indexes::Indexes;
+use crate::FullCrate;
+
+const SCHEMA: &str = "
+CREATE TABLE crates (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    version TEXT NOT NULL,
+    description TEXT NOT NULL,
+    downloads INTEGER NOT NULL,
+    this_version_downloads INTEGER NOT NULL,
+    homepage TEXT,
+    documentation TEXT,
+    repository TEXT,
+    license TEXT NOT NULL,
+    crate_size INTEGER,
+    -- Declared or estimated minimum supported Rust version
+    msrv TEXT,
+    msrv_source TEXT,
+    -- Curated or Legacy
+    provenance TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE chips (
+    crate_id INTEGER NOT NULL REFERENCES crates (id),
+    name TEXT NOT NULL,
+    manufacturer TEXT NOT NULL
+);
+
+CREATE TABLE part_numbers (
+    crate_id INTEGER NOT NULL REFERENCES crates (id),
+    number TEXT NOT NULL,
+    package TEXT,
+    temperature_grade TEXT,
+    lifecycle TEXT,
+    ordering_suffix TEXT
+);
+
+CREATE TABLE packages (
+    crate_id INTEGER NOT NULL REFERENCES crates (id),
+    package TEXT NOT NULL,
+    family TEXT NOT NULL,
+    style TEXT NOT NULL,
+    pins INTEGER NOT NULL,
+    hand_solderable INTEGER NOT NULL
+);
+
+CREATE TABLE kicad_symbols (
+    crate_id INTEGER NOT NULL REFERENCES crates (id),
+    symbol TEXT NOT NULL
+);
+
+-- One row per bus a crate's chips support
+CREATE TABLE interfaces (
+    crate_id INTEGER NOT NULL REFERENCES crates (id),
+    interface TEXT NOT NULL,
+    -- NULL for interfaces without a description, like UART
+    interrupt INTEGER,
+    -- SpiBus or SpiDevice, only for SPI
+    spi_bus_type TEXT
+);
+
+CREATE TABLE i2c_addresses (
+    crate_id INTEGER NOT NULL REFERENCES crates (id),
+    address INTEGER NOT NULL
+);
+
+CREATE TABLE dependencies (
+    crate_id INTEGER NOT NULL REFERENCES crates (id),
+    name TEXT NOT NULL,
+    req TEXT NOT NULL,
+    -- Newest version matching the requirement, NULL if unresolved
+    version TEXT,
+    -- Semver-compatible range, e.g. embedded-hal@1
+    compatible_range TEXT NOT NULL,
+    unresolved TEXT,
+    kind TEXT NOT NULL,
+    optional INTEGER NOT NULL,
+    default_features INTEGER NOT NULL,
+    target TEXT
+);
+
+CREATE TABLE categories (
+    id TEXT PRIMARY KEY,
+    label TEXT NOT NULL,
+    description TEXT NOT NULL,
+    parent_id TEXT REFERENCES categories (id)
+);
+
+-- Every pair of a category and a category it contains, including itself at depth 0
+CREATE TABLE category_closure (
+    ancestor_id TEXT NOT NULL REFERENCES categories (id),
+    descendant_id TEXT NOT NULL REFERENCES categories (id),
+    depth INTEGER NOT NULL,
+    PRIMARY KEY (ancestor_id, descendant_id)
+);
+
+-- Categories as listed by the drivers, use category_closure to include subcategories
+CREATE TABLE crate_categories (
+    crate_id INTEGER NOT NULL REFERENCES crates (id),
+    category_id TEXT NOT NULL REFERENCES categories (id),
+    PRIMARY KEY (crate_id, category_id)
+);
+
+CREATE TABLE boards (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    vendor TEXT NOT NULL,
+    sku TEXT,
+    link TEXT NOT NULL,
+    price_class TEXT
+);
+
+CREATE TABLE board_connectors (
+    board_id TEXT NOT NULL REFERENCES boards (id),
+    connector TEXT NOT NULL,
+    PRIMARY KEY (board_id, connector)
+);
+
+CREATE TABLE crate_boards (
+    crate_id INTEGER NOT NULL REFERENCES crates (id),
+    board_id TEXT NOT NULL REFERENCES boards (id),
+    PRIMARY KEY (crate_id, board_id)
+);
+
+-- Entries of the website indexes, key is NULL for indexes that are plain sets of crates
+CREATE TABLE index_entries (
+    name TEXT NOT NULL,
+    key TEXT,
+    crate_id INTEGER NOT NULL REFERENCES crates (id)
+);
+
+CREATE INDEX chips_name ON chips (name);
+CREATE INDEX part_numbers_number ON part_numbers (number);
+CREATE INDEX dependencies_compatible_range ON dependencies (compatible_range);
+CREATE INDEX index_entries_name_key ON index_entries (name, key);
+";
+
+/// Write the crate database to a new SQLite database at `path`, replacing any existing file
+pub fn write_sqlite(
+    crates: &[FullCrate],
+    indexes: &Indexes,
+    boards: &BoardRegistry,
+    path: &Path,
+) -> anyhow::Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+
+    let mut conn = Connection::open(path)?;
+    let tx = conn.transaction()?;
+    tx.execute_batch(SCHEMA)?;
+
+    insert_categories(&tx)?;
+    insert_boards(&tx, boards)?;
+    for (id, krate) in crates.iter().enumerate() {
+        insert_crate(&tx, id, krate)
+            .with_context(|| format!("Could not export crate {}", krate.name))?;
+    }
+    insert_indexes(&tx, indexes)?;
+
+    tx.commit()?;
+    Ok(())
+}
+
+/// Name of an enum variant as serialized in the JSON output
+fn name(value: &impl Serialize) -> anyhow::Result<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(name) => Ok(name),
+        other => Ok(other.to_string()),
+    }
+}
+
+fn insert_categories(tx: &Transaction) -> anyhow::Result<()> {
+    let mut category_stmt = tx.prepare(
+        "INSERT INTO categories (id, label, description, parent_id) VALUES (?, ?, ?, ?)",
+    )?;
+    let mut closure_stmt = tx.prepare(
+        "INSERT INTO category_closure (ancestor_id, descendant_id, depth) VALUES (?, ?, ?)",
+    )?;
+
+    // Parents come before their children, so the references are always valid
+    for category in Category::all() {
+        category_stmt.execute(params![
+            category.id(),
+            category.label(),
+            category.description(),
+            category.parent().map(|parent| parent.id()),
+        ])?;
+
+        closure_stmt.execute(params![category.id(), category.id(), 0])?;
+        for (depth, ancestor) in category.parents().into_iter().enumerate() {
+            closure_stmt.execute(params![ancestor.id(), category.id(), depth + 1])?;
+        }
+    }
+
+    Ok(())
+}
+
+fn insert_boards(tx: &Transaction, boards: &BoardRegistry) -> anyhow::Result<()> {
+    let mut board_stmt = tx.prepare(
+        "INSERT INTO boards (id, name, vendor, sku, link, price_class) VALUES (?, ?, ?, ?, ?, ?)",
+    )?;
+    let mut connector_stmt =
+        tx.prepare("INSERT OR IGNORE INTO board_connectors (board_id, connector) VALUES (?, ?)")?;
+
+    for board in boards.boards.values() {
+        board_stmt.execute(params![
+            board.id,
+            board.name,
+            board.vendor,
+            board.sku,
+            board.link.as_str(),
+            board.price_class.as_ref().map(name).transpose()?,
+        ])?;
+
+        for connector in &board.connectors {
+            connector_stmt.execute(params![board.id, name(connector)?])?;
+        }
+    }
+
+    Ok(())
+}
+
+fn insert_crate(tx: &Transaction, id: usize, krate: &FullCrate) -> anyhow::Result<()> {
+    tx.execute(
+        "INSERT INTO crates (id, name, version, description, downloads, this_version_downloads,
+            homepage, documentation, repository, license, crate_size, msrv, msrv_source,
+            provenance, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            id,
+            krate.name,
+            krate.version.to_string(),
+            krate.description,
+            krate.downloads,
+            krate.this_version_downloads,
+            krate.homepage,
+            krate.documentation,
+            krate.repository,
+            krate.license,
+            krate.crate_size,
+            krate.msrv.as_ref().map(|msrv| msrv.version.to_string()),
+            krate
+                .msrv
+                .as_ref()
+                .map(|msrv| name(&msrv.source))
+                .transpose()?,
+            name(&krate.provenance)?,
+            krate.created_at.to_rfc3339(),
+            krate.updated_at.to_rfc3339(),
+        ],
+    )?;
+
+    let meta = &krate.chip_meta;
+    let manufacturer = name(&meta.manufacturer)?;
+    for chip in &meta.names {
+        tx.execute(
+            "INSERT INTO chips (crate_id, name, manufacturer) VALUES (?, ?, ?)",
+            params![id, chip, manufacturer],
+        )?;
+    }
+
+    for part in &meta.part_numbers {
+        tx.execute(
+            "INSERT INTO part_numbers
+                (crate_id, number, package, temperature_grade, lifecycle, ordering_suffix)
+            VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                id,
+                part.number,
+                part.package.as_ref().map(ToString::to_string),
+                part.temperature_grade.as_ref().map(name).transpose()?,
+                part.lifecycle.as_ref().map(name).transpose()?,
+                part.ordering_suffix,
+            ],
+        )?;
+    }
+
+    for package in &meta.packages {
+        tx.execute(
+            "INSERT INTO packages (crate_id, package, family, style, pins, hand_solderable)
+            VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                id,
+                package.to_string(),
+                package.family.to_string(),
+                name(&package.family.style())?,
+                package.pins,
+                package.hand_solderable(),
+            ],
+        )?;
+    }
+
+    for symbol in &meta.kicad_symbol {
+        tx.execute(
+            "INSERT INTO kicad_symbols (crate_id, symbol) VALUES (?, ?)",
+            params![id, symbol],
+        )?;
+    }
+
+    if let Some(i2c) = &krate.interfaces.i2c {
+        tx.execute(
+            "INSERT INTO interfaces (crate_id, interface, interrupt) VALUES (?, 'I2C', ?)",
+            params![id, i2c.interrupt],
+        )?;
+        for address in &i2c.addrs {
+            tx.execute(
+                "INSERT INTO i2c_addresses (crate_id, address) VALUES (?, ?)",
+                params![id, address],
+            )?;
+        }
+    }
+    if let Some(spi) = &krate.interfaces.spi {
+        tx.execute(
+            "INSERT INTO interfaces (crate_id, interface, interrupt, spi_bus_type)
+            VALUES (?, 'SPI', ?, ?)",
+            params![id, spi.interrupt, name(&spi.bus_type)?],
+        )?;
+    }
+    for interface in &krate.other_interfaces {
+        tx.execute(
+            "INSERT INTO interfaces (crate_id, interface) VALUES (?, ?)",
+            params![id, name(interface)?],
+        )?;
+    }
+
+    for dep in &krate.dependencies {
+        tx.execute(
+            "INSERT INTO dependencies (crate_id, name, req, version, compatible_range, unresolved,
+                kind, optional, default_features, target)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                id,
+                dep.name,
+                dep.req.to_string(),
+                dep.version.as_ref().map(ToString::to_string),
+                dep.compatible_range(),
+                dep.unresolved.as_ref().map(name).transpose()?,
+                name(&dep.kind)?,
+                dep.optional,
+                dep.default_features,
+                dep.target,
+            ],
+        )?;
+    }
+
+    for category in &meta.categories {
+        tx.execute(
+            "INSERT OR IGNORE INTO crate_categories (crate_id, category_id) VALUES (?, ?)",
+            params![id, category.id()],
+        )?;
+    }
+
+    for board in &krate.dev_boards {
+        tx.execute(
+            "INSERT OR IGNORE INTO crate_boards (crate_id, board_id) VALUES (?, ?)",
+            params![id, board],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn insert_indexes(tx: &Transaction, indexes: &Indexes) -> anyhow::Result<()> {
+    use serde_json::Value;
+
+    let mut stmt =
+        tx.prepare("INSERT INTO index_entries (name, key, crate_id) VALUES (?, ?, ?)")?;
+
+    // Going through the JSON form keeps this in sync with the fields of the indexes
+    let Value::Object(indexes) = serde_json::to_value(indexes)? else {
+        anyhow::bail!("Indexes are not serialized as an object");
+    };
+    for (index, value) in indexes {
+        let entries: Vec<(Option<String>, Value)> = match value {
+            Value::Object(keys) => keys.into_iter().map(|(k, v)| (Some(k), v)).collect(),
+            crates => vec![(None, crates)],
+        };
+
+        for (key, crates) in entries {
+            let Value::Array(crates) = crates else {
+                anyhow::bail!("Index {index} does not list crates");
+            };
+            for krate in crates {
+                stmt.execute(params![index, key, krate.as_u64()])?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,423 @@
+//! TypeScript definitions for the frontend, generated from the JSON schemas of the database
+//!
+//! Only the parts of JSON Schema that schemars produces for our types are supported. The output is
+//! laid out the way prettier formats it, so the generated file passes the frontend format check.
+
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Context};
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde_json::{Map, Value};
+
+use crate::website_db::split::{CrateSummary, SplitManifest};
+use crate::FullCrateDb;
+
+/// Line width prettier wraps at
+const WIDTH: usize = 80;
+
+const HEADER: &str = "/* eslint-disable */
+/**
+ * This file was automatically generated from the JSON schemas of the crate database.
+ * DO NOT MODIFY IT BY HAND. Instead, change the Rust types in `backend` and run
+ * `cargo run --bin read-driver-db -- typescript` to regenerate this file.
+ */
+";
+
+/// Definitions for `full-crate-db.json` and the split database
+///
+/// The schemas describe the types as they are serialized, as that is what the frontend reads.
+pub fn crate_db() -> anyhow::Result<String> {
+    let mut generator = SchemaSettings::default().for_serialize().into_generator();
+    let roots = [
+        root::<FullCrateDb>(&mut generator)?,
+        root::<SplitManifest>(&mut generator)?,
+        root::<CrateSummary>(&mut generator)?,
+    ];
+    let definitions = generator.take_definitions(true);
+
+    generate(&definitions, &roots)
+}
+
+/// Name of the definition of `T`
+fn root<T: JsonSchema>(generator: &mut SchemaGenerator) -> anyhow::Result<String> {
+    let schema: Schema = generator.subschema_for::<T>();
+    match schema.get("$ref").and_then(Value::as_str) {
+        Some(reference) => reference_name(reference),
+        None => bail!("{} has no definition of its own", T::schema_name()),
+    }
+}
+
+/// TypeScript declarations for `roots` and all definitions they use, in order of first use
+pub fn generate(definitions: &Map<String, Value>, roots: &[String]) -> anyhow::Result<String> {
+    let mut output = HEADER.to_owned();
+    let mut queue: Vec<String> = roots.to_vec();
+    let mut seen: BTreeSet<String> = roots.iter().cloned().collect();
+    let mut names = BTreeSet::new();
+
+    let mut i = 0;
+    while let Some(name) = queue.get(i).cloned() {
+        i += 1;
+
+        let schema = definitions
+            .get(&name)
+            .with_context(|| format!("No definition of {name}"))?;
+        let ty = convert(schema).with_context(|| format!("Could not convert {name}"))?;
+
+        let mut references = vec![];
+        ty.references(&mut references);
+        for reference in references {
+            if seen.insert(reference.clone()) {
+                queue.push(reference);
+            }
+        }
+
+        let identifier = identifier(&name);
+        if !names.insert(identifier.clone()) {
+            bail!("Multiple definitions are called {identifier} in TypeScript");
+        }
+
+        output.push('\n');
+        output.push_str(&doc_comment(description(schema), 0));
+        match &ty {
+            Ty::Object(..) => {
+                output.push_str(&format!(
+                    "export interface {identifier} {}\n",
+                    ty.print(0, 0, 0)
+                ));
+            }
+            _ => {
+                let prefix = format!("export type {identifier} =");
+                output.push_str(&assign(&prefix, &ty, 0));
+                output.push_str(";\n");
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// TypeScript type expression
+#[derive(Debug, Clone, PartialEq)]
+enum Ty {
+    /// Built-in type or literal
+    Plain(String),
+    /// Other definition, by its name in the schema
+    Ref(String),
+    Array(Box<Ty>),
+    Tuple(Vec<Ty>),
+    Union(Vec<Ty>),
+    /// Object with properties and the type of all other properties
+    Object(Vec<Property>, Option<Box<Ty>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Property {
+    name: String,
+    description: Option<String>,
+    optional: bool,
+    ty: Ty,
+}
+
+fn convert(schema: &Value) -> anyhow::Result<Ty> {
+    let schema = match schema {
+        Value::Bool(true) => return Ok(Ty::Plain("unknown".into())),
+        Value::Bool(false) => return Ok(Ty::Plain("never".into())),
+        Value::Object(schema) => schema,
+        _ => bail!("Schema is not an object: {schema}"),
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return Ok(Ty::Ref(reference_name(reference)?));
+    }
+    if let Some(value) = schema.get("const") {
+        return Ok(Ty::Plain(value.to_string()));
+    }
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        return Ok(union(
+            values.iter().map(|v| Ty::Plain(v.to_string())).collect(),
+        ));
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(Value::Array(variants)) = schema.get(key) {
+            return Ok(union(
+                variants.iter().map(convert).collect::<Result<_, _>>()?,
+            ));
+        }
+    }
+    if let Some(Value::Array(all)) = schema.get("allOf") {
+        if let [only] = all.as_slice() {
+            return convert(only);
+        }
+        bail!("allOf with multiple schemas is not supported");
+    }
+
+    match schema.get("type") {
+        Some(Value::String(ty)) => convert_type(ty, schema),
+        Some(Value::Array(types)) => Ok(union(
+            types
+                .iter()
+                .map(|ty| match ty.as_str() {
+                    Some(ty) => convert_type(ty, schema),
+                    None => bail!("Type is not a string: {ty}"),
+                })
+                .collect::<Result<_, _>>()?,
+        )),
+        Some(ty) => bail!("Type is not a string: {ty}"),
+        None => Ok(Ty::Plain("unknown".into())),
+    }
+}
+
+fn convert_type(ty: &str, schema: &Map<String, Value>) -> anyhow::Result<Ty> {
+    Ok(match ty {
+        "string" => Ty::Plain("string".into()),
+        "integer" | "number" => Ty::Plain("number".into()),
+        "boolean" => Ty::Plain("boolean".into()),
+        "null" => Ty::Plain("null".into()),
+        "array" => {
+            if let Some(Value::Array(items)) = schema.get("prefixItems") {
+                return Ok(Ty::Tuple(
+                    items.iter().map(convert).collect::<Result<_, _>>()?,
+                ));
+            }
+
+            let items = match schema.get("items") {
+                Some(items) => convert(items)?,
+                None => Ty::Plain("unknown".into()),
+            };
+            let min = schema.get("minItems").and_then(Value::as_u64);
+            let max = schema.get("maxItems").and_then(Value::as_u64);
+            match (min, max) {
+                (Some(min), Some(max)) if min == max && max <= 4 => {
+                    Ty::Tuple(vec![items; max as usize])
+                }
+                _ => Ty::Array(Box::new(items)),
+            }
+        }
+        "object" => {
+            let required: BTreeSet<&str> = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+
+            let mut properties = vec![];
+            if let Some(Value::Object(props)) = schema.get("properties") {
+                for (name, prop) in props {
+                    properties.push(Property {
+                        name: name.clone(),
+                        description: description(prop).map(str::to_owned),
+                        optional: !required.contains(name.as_str()),
+                        ty: convert(prop).with_context(|| format!("Property {name}"))?,
+                    });
+                }
+            }
+
+            let pattern = schema
+                .get("patternProperties")
+                .and_then(Value::as_object)
+                .and_then(|patterns| patterns.values().next());
+            let rest = match (schema.get("additionalProperties"), pattern) {
+                (_, Some(rest)) => Some(convert(rest)?),
+                (Some(Value::Bool(false)), None) => None,
+                (Some(Value::Bool(true)) | None, None) if !properties.is_empty() => None,
+                (Some(rest), None) => Some(convert(rest)?),
+                (None, None) => Some(Ty::Plain("unknown".into())),
+            };
+
+            Ty::Object(properties, rest.map(Box::new))
+        }
+        _ => bail!("Unsupported type {ty}"),
+    })
+}
+
+/// Union of `members`, with nested unions flattened and duplicates removed
+fn union(members: Vec<Ty>) -> Ty {
+    let mut flat: Vec<Ty> = vec![];
+    for member in members {
+        let nested = match member {
+            Ty::Union(nested) => nested,
+            member => vec![member],
+        };
+        for member in nested {
+            if !flat.contains(&member) {
+                flat.push(member);
+            }
+        }
+    }
+
+    match <[Ty; 1]>::try_from(flat) {
+        Ok([only]) => only,
+        Err(flat) => Ty::Union(flat),
+    }
+}
+
+impl Ty {
+    /// Definitions used by this type, in order of appearance
+    fn references(&self, references: &mut Vec<String>) {
+        match self {
+            Ty::Plain(_) => {}
+            Ty::Ref(name) => references.push(name.clone()),
+            Ty::Array(items) => items.references(references),
+            Ty::Tuple(members) | Ty::Union(members) => {
+                for member in members {
+                    member.references(references);
+                }
+            }
+            Ty::Object(properties, rest) => {
+                for property in properties {
+                    property.ty.references(references);
+                }
+                if let Some(rest) = rest {
+                    rest.references(references);
+                }
+            }
+        }
+    }
+
+    /// This type on a single line, if it can be written on one
+    fn flat(&self) -> Option<String> {
+        Some(match self {
+            Ty::Plain(plain) => plain.clone(),
+            Ty::Ref(name) => identifier(name),
+            Ty::Array(items) => match **items {
+                Ty::Union(_) => format!("({})[]", items.flat()?),
+                _ => format!("{}[]", items.flat()?),
+            },
+            Ty::Tuple(members) => format!(
+                "[{}]",
+                members
+                    .iter()
+                    .map(Ty::flat)
+                    .collect::<Option<Vec<_>>>()?
+                    .join(", ")
+            ),
+            Ty::Union(members) => members
+                .iter()
+                .map(Ty::flat)
+                .collect::<Option<Vec<_>>>()?
+                .join(" | "),
+            Ty::Object(..) => return None,
+        })
+    }
+
+    /// This type starting at `column`, followed by `suffix` more characters on the same line
+    ///
+    /// Long unions are broken into one member per line, starting on a new line indented one level
+    /// deeper than `indent`.
+    fn print(&self, indent: usize, column: usize, suffix: usize) -> String {
+        if let Some(flat) = self.flat() {
+            if column + flat.len() + suffix <= WIDTH {
+                return flat;
+            }
+        }
+
+        let pad = " ".repeat(indent);
+        match self {
+            Ty::Union(members) => members
+                .iter()
+                .map(|member| {
+                    let start = indent + 4;
+                    format!("\n{pad}  | {}", member.print(indent + 2, start, 0))
+                })
+                .collect(),
+            Ty::Array(items) => match &**items {
+                Ty::Union(_) => format!("({}\n{pad})[]", items.print(indent, column, 0)),
+                _ => format!("{}[]", items.print(indent, column, suffix + 2)),
+            },
+            Ty::Object(properties, rest) => {
+                let inner = indent + 2;
+                let inner_pad = " ".repeat(inner);
+                let mut output = "{\n".to_owned();
+                for property in properties {
+                    output.push_str(&doc_comment(property.description.as_deref(), inner));
+                    let key = format!(
+                        "{inner_pad}{}{}:",
+                        key(&property.name),
+                        if property.optional { "?" } else { "" }
+                    );
+                    output.push_str(&assign(&key, &property.ty, inner));
+                    output.push_str(";\n");
+                }
+                if let Some(rest) = rest {
+                    output.push_str(&assign(&format!("{inner_pad}[k: string]:"), rest, inner));
+                    output.push_str(";\n");
+                }
+                output.push_str(&pad);
+                output.push('}');
+                output
+            }
+            // Nothing else can get long enough to be broken up
+            _ => self.flat().unwrap_or_default(),
+        }
+    }
+}
+
+/// `prefix` followed by `ty` and a semicolon, like a property or type alias
+fn assign(prefix: &str, ty: &Ty, indent: usize) -> String {
+    let printed = ty.print(indent, prefix.len() + 1, 1);
+    if printed.starts_with('\n') {
+        format!("{prefix}{printed}")
+    } else {
+        format!("{prefix} {printed}")
+    }
+}
+
+/// JSDoc comment for `description`, if there is one
+fn doc_comment(description: Option<&str>, indent: usize) -> String {
+    let Some(description) = description else {
+        return String::new();
+    };
+
+    let pad = " ".repeat(indent);
+    let mut comment = format!("{pad}/**\n");
+    for line in description.lines() {
+        let line = line.trim_end().replace("*/", "*\\/");
+        if line.is_empty() {
+            comment.push_str(&format!("{pad} *\n"));
+        } else {
+            comment.push_str(&format!("{pad} * {line}\n"));
+        }
+    }
+    comment.push_str(&format!("{pad} */\n"));
+    comment
+}
+
+fn description(schema: &Value) -> Option<&str> {
+    schema.get("description").and_then(Value::as_str)
+}
+
+/// Name of the definition `reference` points to
+fn reference_name(reference: &str) -> anyhow::Result<String> {
+    match reference.strip_prefix("#/$defs/") {
+        Some(name) => Ok(name.to_owned()),
+        None => bail!("Unsupported reference {reference}"),
+    }
+}
+
+/// Definition name as a TypeScript identifier, e.g. `IndexForCategory` for `Index_for_Category`
+fn identifier(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Property name, quoted if it is not a valid identifier
+fn key(name: &str) -> String {
+    let valid = name.chars().enumerate().all(|(i, c)| {
+        c.is_ascii_alphabetic() || c == '_' || c == '$' || (i > 0 && c.is_ascii_digit())
+    });
+    if valid && !name.is_empty() {
+        name.to_owned()
+    } else {
+        Value::from(name).to_string()
+    }
+}
@@ -0,0 +1,250 @@
+//! Atom and JSON feeds of newly listed drivers and new releases
+//!
+//! `all.xml` (Atom) and `all.json` (JSON Feed) cover all drivers. Every top-level category gets its
+//! own pair of feeds as well, e.g. `display.xml` and `display.json` for new display drivers.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use url::Url;
+
+use crate::driver_db::categories::Category;
+use crate::FullCrate;
+
+/// Number of entries in each feed, newest first
+const MAX_ENTRIES: usize = 50;
+
+const TITLE: &str = "New and updated Rust drivers";
+
+/// Something that happened to a driver
+#[derive(Debug, Clone, Copy)]
+struct Entry<'a> {
+    krate: &'a FullCrate,
+    /// Is this the first release rather than a new version?
+    new: bool,
+    date: DateTime<Utc>,
+}
+
+impl Entry<'_> {
+    fn title(&self) -> String {
+        let FullCrate { name, version, .. } = self.krate;
+        // Only the latest version is known, which need not be the first one
+        if self.new {
+            format!("New driver: {name}")
+        } else {
+            format!("{name} {version} released")
+        }
+    }
+
+    /// Unique and stable id, the crates.io page of the crate or of its new version
+    fn url(&self) -> String {
+        let FullCrate { name, version, .. } = self.krate;
+        if self.new {
+            format!("https://crates.io/crates/{name}")
+        } else {
+            format!("https://crates.io/crates/{name}/{version}")
+        }
+    }
+
+    fn categories(&self) -> Vec<String> {
+        self.krate
+            .chip_meta
+            .categories
+            .iter()
+            .map(|category| category.to_string())
+            .collect()
+    }
+}
+
+/// Entries for the first and the latest release of `crates`, newest first
+fn entries<'a>(crates: &[&'a FullCrate]) -> Vec<Entry<'a>> {
+    let mut entries = vec![];
+    for krate in crates {
+        entries.push(Entry {
+            krate,
+            new: true,
+            date: krate.created_at,
+        });
+        // Metadata changes update the crate too, only count versions published after the first one
+        if krate.released_at > krate.created_at {
+            entries.push(Entry {
+                krate,
+                new: false,
+                date: krate.released_at,
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| {
+        b.date
+            .cmp(&a.date)
+            .then_with(|| a.krate.name.cmp(&b.krate.name))
+    });
+    entries.truncate(MAX_ENTRIES);
+    entries
+}
+
+/// Top-level category `krate` is listed under
+fn is_in(krate: &FullCrate, top: Category) -> bool {
+    krate
+        .chip_meta
+        .categories
+        .iter()
+        .any(|category| *category == top || category.parents().contains(&top))
+}
+
+/// Write all feeds into `dir`, which is published as `feeds/` of the website at `site`
+///
+/// Returns the number of feeds written.
+pub fn write_feeds(dir: &Path, crates: &[FullCrate], site: &Url) -> anyhow::Result<usize> {
+    std::fs::create_dir_all(dir)?;
+
+    let mut site = site.clone();
+    if !site.path().ends_with('/') {
+        site.set_path(&format!("{}/", site.path()));
+    }
+    let feeds = site.join("feeds/")?;
+
+    let mut written = 0;
+    let mut write = |stem: &str, title: &str, crates: &[&FullCrate]| -> anyhow::Result<()> {
+        let entries = entries(crates);
+
+        let atom_url = feeds.join(&format!("{stem}.xml"))?;
+        std::fs::write(
+            dir.join(format!("{stem}.xml")),
+            atom(title, &site, &atom_url, &entries)?,
+        )?;
+
+        let json_url = feeds.join(&format!("{stem}.json"))?;
+        std::fs::write(
+            dir.join(format!("{stem}.json")),
+            serde_json::to_string_pretty(&json_feed(title, &site, &json_url, &entries))?,
+        )?;
+
+        written += 2;
+        Ok(())
+    };
+
+    let all: Vec<_> = crates.iter().collect();
+    write("all", TITLE, &all)?;
+
+    for top in Category::all().filter(|category| category.parent().is_none()) {
+        let in_category: Vec<_> = crates.iter().filter(|krate| is_in(krate, top)).collect();
+        write(
+            &top.id().to_lowercase(),
+            &format!("{TITLE}: {}", top.label()),
+            &in_category,
+        )?;
+    }
+
+    Ok(written)
+}
+
+fn date(date: &DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Atom feed, see RFC 4287
+fn atom(
+    title: &str,
+    site: &Url,
+    feed_url: &Url,
+    entries: &[Entry],
+) -> Result<String, std::fmt::Error> {
+    // The feed is as new as its newest entry, so it only changes when an entry does
+    let updated = entries
+        .first()
+        .map_or_else(|| DateTime::UNIX_EPOCH, |entry| entry.date);
+
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+    writeln!(xml, r#"<feed xmlns="http://www.w3.org/2005/Atom">"#)?;
+    writeln!(xml, "  <title>{}</title>", escape(title))?;
+    writeln!(xml, "  <id>{}</id>", escape(feed_url.as_str()))?;
+    writeln!(
+        xml,
+        r#"  <link rel="self" href="{}"/>"#,
+        escape(feed_url.as_str())
+    )?;
+    writeln!(xml, r#"  <link href="{}"/>"#, escape(site.as_str()))?;
+    writeln!(xml, "  <updated>{}</updated>", date(&updated))?;
+    writeln!(xml, "  <author><name>drive-rs</name></author>")?;
+
+    for entry in entries {
+        let url = escape(&entry.url());
+        writeln!(xml, "  <entry>")?;
+        writeln!(xml, "    <title>{}</title>", escape(&entry.title()))?;
+        writeln!(xml, "    <id>{url}</id>")?;
+        writeln!(xml, r#"    <link href="{url}"/>"#)?;
+        writeln!(xml, "    <updated>{}</updated>", date(&entry.date))?;
+        writeln!(
+            xml,
+            "    <summary>{}</summary>",
+            escape(entry.krate.description.trim())
+        )?;
+        for category in entry.categories() {
+            writeln!(xml, r#"    <category term="{}"/>"#, escape(&category))?;
+        }
+        writeln!(xml, "  </entry>")?;
+    }
+
+    xml.push_str("</feed>\n");
+    Ok(xml)
+}
+
+/// JSON Feed, see <https://www.jsonfeed.org/version/1.1/>
+#[derive(Debug, Serialize)]
+struct JsonFeed {
+    version: &'static str,
+    title: String,
+    home_page_url: String,
+    feed_url: String,
+    items: Vec<JsonFeedItem>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonFeedItem {
+    id: String,
+    url: String,
+    title: String,
+    content_text: String,
+    date_published: String,
+    tags: Vec<String>,
+}
+
+fn json_feed(title: &str, site: &Url, feed_url: &Url, entries: &[Entry]) -> JsonFeed {
+    JsonFeed {
+        version: "https://jsonfeed.org/version/1.1",
+        title: title.to_owned(),
+        home_page_url: site.to_string(),
+        feed_url: feed_url.to_string(),
+        items: entries
+            .iter()
+            .map(|entry| JsonFeedItem {
+                id: entry.url(),
+                url: entry.url(),
+                title: entry.title(),
+                content_text: entry.krate.description.trim().to_owned(),
+                date_published: date(&entry.date),
+                tags: entry.categories(),
+            })
+            .collect(),
+    }
+}
@@ -1,14 +1,27 @@
-use super::{Interface, WebsiteCrate};
-use crate::driver_db::categories::Category;
-use crate::driver_db::packages::PackageType;
-use crate::driver_db::Interfaces;
-use crate::FullCrate;
+use crate::driver_db::categories::{Category, CategoryNode};
+use crate::driver_db::connectors::Connection;
+use crate::driver_db::electrical::{SupplyClass, TemperatureGrade};
+use crate::driver_db::measurements::MeasuredQuantity;
+use crate::driver_db::packages::{PackageFamily, PackageStyle};
+use crate::website_db::Interface;
+use crate::{FullCrate, Provenance};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 
+/// Positions of the crates in the crate list, by key
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
-pub struct Index<T: Ord>(pub BTreeMap<T, BTreeSet<usize>>);
+#[schemars(rename = "Index_for_{T}")]
+pub struct Index<T: Ord>(
+    // Keys are always strings in JSON, whatever they are in Rust
+    #[schemars(with = "BTreeMap<String, BTreeSet<usize>>")] pub BTreeMap<T, BTreeSet<usize>>,
+);
+
+impl<T: Ord> Default for Index<T> {
+    fn default() -> Self {
+        Self(BTreeMap::new())
+    }
+}
 
 impl<T: Ord + Clone> Index<T> {
     fn new() -> Self {
@@ -24,12 +37,59 @@ impl<T: Ord + Clone> Index<T> {
 pub struct Indexes {
     pub category: Index<Category>,
     pub license: Index<String>,
-    pub rust_version: Index<String>,
+    /// Declared or estimated minimum supported Rust version
+    pub rust_version: Index<semver::Version>,
+    /// Crates whose minimum supported Rust version was estimated
+    pub rust_version_estimated: BTreeSet<usize>,
+    /// Normal dependencies that are always enabled, by semver-compatible range
     pub dependencies: Index<String>,
+    /// Normal dependencies that are always enabled, by crate name
+    pub dependency_names: Index<String>,
+    /// Normal dependencies that are enabled by a feature, by semver-compatible range
+    pub optional_dependencies: Index<String>,
     pub interfaces: Index<Interface>,
-    pub package: Index<PackageType>,
+    /// Curated drivers and those only in the legacy list
+    pub provenance: Index<Provenance>,
+    pub package: Index<PackageFamily>,
+    /// How the packages connect to the board
+    pub package_style: Index<PackageStyle>,
+    /// Crates with at least one chip package that can be soldered by hand
+    pub hand_solderable: BTreeSet<usize>,
+    /// Quantities with a measurement specification
+    pub measured_quantity: Index<MeasuredQuantity>,
+    /// Supply situations the chips can be used in, derived from their electrical characteristics
+    pub supply: Index<SupplyClass>,
+    /// Standard temperature grades covered by the operating temperature range
+    pub temperature_grade: Index<TemperatureGrade>,
+    /// Crates with KiCad symbols, only counting symbols that exist if the KiCad libraries were checked
     pub has_kicad: BTreeSet<usize>,
     pub has_dev_board: BTreeSet<usize>,
+    /// Crates supporting a chip on a development board, by board id
+    pub board: Index<String>,
+    /// Crates with a development board that plugs into a connector, by the connector of the host
+    ///
+    /// Add-ons are listed under their host, e.g. FeatherWings under Feather. Cable connectors include
+    /// compatible ones, e.g. boards with STEMMA QT are listed under Qwiic as well.
+    pub connector: Index<Connection>,
+}
+
+impl Indexes {
+    /// Tree of all categories with the number of crates in each
+    pub fn category_tree(&self) -> Vec<CategoryNode> {
+        CategoryNode::tree(&|category| self.category.0.get(&category).map_or(0, BTreeSet::len))
+    }
+
+    /// Crates whose minimum supported Rust version is at most `toolchain`
+    ///
+    /// Crates without a known minimum supported Rust version are not included.
+    pub fn builds_on(&self, toolchain: &semver::Version) -> BTreeSet<usize> {
+        self.rust_version
+            .0
+            .range(..=toolchain)
+            .flat_map(|(_, crates)| crates)
+            .copied()
+            .collect()
+    }
 }
 
 impl From<&[FullCrate]> for Indexes {
@@ -37,11 +97,22 @@ impl From<&[FullCrate]> for Indexes {
         let mut category = Index::new();
         let mut license = Index::new();
         let mut rust_version = Index::new();
+        let mut rust_version_estimated = BTreeSet::new();
         let mut dependencies = Index::new();
+        let mut dependency_names = Index::new();
+        let mut optional_dependencies = Index::new();
         let mut interfaces = Index::new();
+        let mut provenance = Index::new();
         let mut package = Index::new();
+        let mut package_style = Index::new();
+        let mut hand_solderable = BTreeSet::new();
+        let mut measured_quantity = Index::new();
+        let mut supply = Index::new();
+        let mut temperature_grade = Index::new();
         let mut has_kicad = BTreeSet::new();
         let mut has_dev_board = BTreeSet::new();
+        let mut board = Index::new();
+        let mut connector = Index::new();
 
         for (i, krate) in value.iter().enumerate() {
             for cat in &krate.chip_meta.categories {
@@ -56,92 +127,90 @@ impl From<&[FullCrate]> for Indexes {
                 license.add(l, i);
             }
 
-            rust_version.add(
-                krate
-                    .rust_version
-                    .as_ref()
-                    .map(|v| v.to_string())
-                    .unwrap_or(String::new()),
-                i,
-            );
+            if let Some(msrv) = &krate.msrv {
+                rust_version.add(msrv.version.clone(), i);
 
-            for dep in &krate.dependencies {
-                dependencies.add(dep.to_string(), i);
+                if msrv.is_estimated() {
+                    rust_version_estimated.insert(i);
+                }
             }
 
-            let Interfaces { i2c, spi } = &krate.interfaces;
-            if i2c.is_some() {
-                interfaces.add(Interface::I2C, i);
+            for dep in &krate.dependencies {
+                if dep.is_required() {
+                    dependencies.add(dep.compatible_range(), i);
+                    dependency_names.add(dep.name.clone(), i);
+                } else if dep.is_optional() {
+                    optional_dependencies.add(dep.compatible_range(), i);
+                }
             }
-            if spi.is_some() {
-                interfaces.add(Interface::SPI, i);
+
+            for interface in krate.all_interfaces() {
+                interfaces.add(interface, i);
             }
 
+            provenance.add(krate.provenance, i);
+
             for p in &krate.chip_meta.packages {
-                package.add(p.ty, i);
-            }
+                package.add(p.family, i);
+                package_style.add(p.family.style(), i);
 
-            if !krate.chip_meta.kicad_symbol.is_empty() {
-                has_kicad.insert(i);
+                if p.hand_solderable() {
+                    hand_solderable.insert(i);
+                }
             }
 
-            if !krate.dev_boards.is_empty() {
-                has_dev_board.insert(i);
+            for m in &krate.chip_meta.measurements {
+                measured_quantity.add(m.quantity, i);
             }
-        }
 
-        Self {
-            category,
-            license,
-            rust_version,
-            dependencies,
-            interfaces,
-            package,
-            has_kicad,
-            has_dev_board,
-        }
-    }
-}
-
-impl From<&[WebsiteCrate]> for Indexes {
-    fn from(value: &[WebsiteCrate]) -> Self {
-        let mut license = Index::new();
-        let mut rust_version = Index::new();
-        let mut dependencies = Index::new();
-        let mut interfaces = Index::new();
+            if let Some(electrical) = &krate.chip_meta.electrical {
+                for class in electrical.supply_classes() {
+                    supply.add(class, i);
+                }
+                for grade in electrical.temperature_grades() {
+                    temperature_grade.add(grade, i);
+                }
+            }
 
-        for (i, krate) in value.iter().enumerate() {
-            for l in krate.licenses() {
-                license.add(l, i);
+            if krate.has_kicad() {
+                has_kicad.insert(i);
             }
 
-            rust_version.add(
-                krate
-                    .rust_version
-                    .as_ref()
-                    .map(|v| v.to_string())
-                    .unwrap_or(String::new()),
-                i,
-            );
+            if !krate.dev_boards.is_empty() {
+                has_dev_board.insert(i);
+            }
 
-            for dep in &krate.dependencies {
-                dependencies.add(dep.to_string(), i);
+            for id in &krate.dev_boards {
+                board.add(id.clone(), i);
             }
 
-            for interface in &krate.interfaces {
-                interfaces.add(*interface, i);
+            for c in &krate.connectors {
+                for host in c.fits_into() {
+                    connector.add(host, i);
+                }
             }
         }
 
         Self {
-            category: Index::new(),
+            category,
             license,
             rust_version,
+            rust_version_estimated,
             dependencies,
+            dependency_names,
+            optional_dependencies,
             interfaces,
-            package: Index::new(),
-            has_kicad: BTreeSet::new(),
-            has_dev_board: BTreeSet::new(),
+            provenance,
+            package,
+            package_style,
+            hand_solderable,
+            measured_quantity,
+            supply,
+            temperature_grade,
+            has_kicad,
+            has_dev_board,
+            board,
+            connector,
         }
     }
 }
@@ -0,0 +1,353 @@
+//! Full-text search over the crate database, precomputed by the generator
+//!
+//! Text is split into lowercase tokens, and plurals and `-ing` and `-ed` forms are reduced to a
+//! common stem, so `displays` finds `display`. Chip names and part numbers are kept whole as well,
+//! so ordering codes like `INA219AIDCNR` find the `INA219` driver and partial numbers like `INA21`
+//! find it too.
+//!
+//! The frontend mirrors [`tokenize`] and [`SearchIndex::search`] in `frontend/src/search.ts`.
+
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::FullCrate;
+
+/// Weights of the fields a term was found in
+const NAME_WEIGHT: u32 = 10;
+const CHIP_WEIGHT: u32 = 8;
+const PART_NUMBER_WEIGHT: u32 = 6;
+const MANUFACTURER_WEIGHT: u32 = 3;
+const CATEGORY_WEIGHT: u32 = 3;
+const DESCRIPTION_WEIGHT: u32 = 1;
+const RESOURCE_WEIGHT: u32 = 1;
+
+/// Shortest query token that is matched as a prefix of terms
+const MIN_PREFIX_LEN: usize = 3;
+/// Shortest part number that is matched as a prefix of a query, like a chip name in an ordering code
+const MIN_PART_NUMBER_LEN: usize = 4;
+
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "for", "in", "of", "on", "or", "the", "to", "with",
+];
+
+/// Inverted index from terms to the crates containing them
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SearchIndex {
+    /// Weight of each crate containing a term, by stemmed term
+    pub terms: BTreeMap<String, BTreeMap<usize, u32>>,
+    /// Weight of each crate supporting a chip, by chip name or part number
+    ///
+    /// Numbers are lowercase and without punctuation, e.g. `scd40dr2` for `SCD40-D-R2`.
+    pub part_numbers: BTreeMap<String, BTreeMap<usize, u32>>,
+}
+
+impl SearchIndex {
+    fn add_term(&mut self, term: String, krate: usize, weight: u32) {
+        *self
+            .terms
+            .entry(term)
+            .or_default()
+            .entry(krate)
+            .or_default() += weight;
+    }
+
+    fn add_text(&mut self, text: &str, krate: usize, weight: u32) {
+        for token in tokenize(text) {
+            self.add_term(token, krate, weight);
+        }
+    }
+
+    fn add_part_number(&mut self, number: &str, krate: usize, weight: u32) {
+        let number = normalize_part_number(number);
+        if number.is_empty() {
+            return;
+        }
+
+        let weights = self.part_numbers.entry(number).or_default();
+        let entry = weights.entry(krate).or_default();
+        *entry = (*entry).max(weight);
+    }
+
+    /// Crates matching `query` with their relevance, most relevant first
+    ///
+    /// Each query token adds the weight of exact matches, half of it for terms it is a prefix of,
+    /// and the weight of part numbers the token or the whole query starts with.
+    pub fn search(&self, query: &str) -> Vec<(usize, u32)> {
+        let mut scores: BTreeMap<usize, u32> = BTreeMap::new();
+
+        for token in tokenize(query) {
+            if let Some(crates) = self.terms.get(&token) {
+                for (krate, weight) in crates {
+                    *scores.entry(*krate).or_default() += weight * 2;
+                }
+            }
+
+            if token.len() >= MIN_PREFIX_LEN {
+                let longer = self
+                    .terms
+                    .range(token.clone()..)
+                    .skip_while(|(term, _)| **term == token)
+                    .take_while(|(term, _)| term.starts_with(&token));
+                for (_, crates) in longer {
+                    for (krate, weight) in crates {
+                        *scores.entry(*krate).or_default() += weight;
+                    }
+                }
+            }
+        }
+
+        let mut candidates: Vec<_> = query
+            .split_whitespace()
+            .map(normalize_part_number)
+            .collect();
+        candidates.push(normalize_part_number(query));
+        candidates.sort();
+        candidates.dedup();
+        for candidate in candidates {
+            for (krate, weight) in self.match_part_number(&candidate) {
+                *scores.entry(krate).or_default() += weight * 2;
+            }
+        }
+
+        let mut results: Vec<_> = scores.into_iter().collect();
+        results.sort_by(|(a, score_a), (b, score_b)| score_b.cmp(score_a).then(a.cmp(b)));
+        results
+    }
+
+    /// Part numbers that start with `query` or that `query` starts with
+    fn match_part_number(&self, query: &str) -> BTreeMap<usize, u32> {
+        let mut matches = BTreeMap::new();
+        if query.len() < MIN_PREFIX_LEN {
+            return matches;
+        }
+
+        let longer = self
+            .part_numbers
+            .range(query.to_owned()..)
+            .take_while(|(number, _)| number.starts_with(query));
+        let shorter = (MIN_PART_NUMBER_LEN..query.len())
+            .filter_map(|len| query.get(..len))
+            .filter_map(|prefix| self.part_numbers.get_key_value(prefix));
+
+        for (_, crates) in longer.chain(shorter) {
+            for (krate, weight) in crates {
+                let entry = matches.entry(*krate).or_default();
+                *entry = (*entry).max(*weight);
+            }
+        }
+        matches
+    }
+}
+
+impl From<&[FullCrate]> for SearchIndex {
+    fn from(crates: &[FullCrate]) -> Self {
+        let mut index = Self::default();
+
+        for (i, krate) in crates.iter().enumerate() {
+            let meta = &krate.chip_meta;
+
+            index.add_text(&krate.name, i, NAME_WEIGHT);
+            if krate.name.contains(|c: char| !c.is_alphanumeric()) {
+                index.add_term(krate.name.to_lowercase(), i, NAME_WEIGHT);
+            }
+            index.add_text(&krate.description, i, DESCRIPTION_WEIGHT);
+            for resource in &krate.resources {
+                index.add_text(&resource.title, i, RESOURCE_WEIGHT);
+            }
+
+            for name in &meta.names {
+                index.add_text(name, i, CHIP_WEIGHT);
+                index.add_part_number(name, i, CHIP_WEIGHT);
+            }
+            for part in &meta.part_numbers {
+                index.add_part_number(&part.number, i, PART_NUMBER_WEIGHT);
+                index.add_part_number(part.base_number(), i, PART_NUMBER_WEIGHT);
+            }
+
+            if let Ok(serde_json::Value::String(manufacturer)) =
+                serde_json::to_value(&meta.manufacturer)
+            {
+                index.add_text(&manufacturer, i, MANUFACTURER_WEIGHT);
+            }
+
+            for category in &meta.categories {
+                for category in std::iter::once(*category).chain(category.parents()) {
+                    index.add_text(category.label(), i, CATEGORY_WEIGHT);
+                }
+            }
+        }
+
+        index
+    }
+}
+
+/// Split text into lowercase, stemmed tokens, leaving out stop words
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .map(|word| stem(&word))
+        .collect()
+}
+
+/// Lowercase part number without separators, e.g. `scd40dr2` for `SCD40-D-R2`
+pub fn normalize_part_number(number: &str) -> String {
+    number
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Strip inflectional suffixes, so different forms of a word share a stem
+///
+/// This is a small subset of the Porter stemmer, enough for names of chips and their functions:
+/// plurals and `-ing` and `-ed` forms are reduced, so `sensors` and `sensor`, `controlled` and
+/// `controlling`, `meters` and `metering` match. Other endings are part of the word, `meter`,
+/// `speed` and `power` stay as they are. A final `e` is dropped, so `measure` and `measured` match.
+/// Tokens containing digits are kept as they are, as they are most likely part numbers.
+pub fn stem(word: &str) -> String {
+    if word.chars().any(|c| c.is_ascii_digit()) || !word.is_ascii() {
+        return word.to_owned();
+    }
+
+    let word = strip_plural(word);
+    let word = strip_inflection(&word);
+    strip_final_e(&word)
+}
+
+/// Shortest stem left after removing a suffix
+const MIN_STEM: usize = 3;
+
+fn has_vowel(stem: &str) -> bool {
+    stem.contains(['a', 'e', 'i', 'o', 'u', 'y'])
+}
+
+/// `batteries` -> `battery`, `classes` -> `class`, `sensors` -> `sensor`
+fn strip_plural(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix("ies") {
+        if stem.len() >= MIN_STEM - 1 {
+            return format!("{stem}y");
+        }
+    }
+    if let Some(stem) = word.strip_suffix("sses") {
+        return format!("{stem}ss");
+    }
+    // Words like `bus`, `status` and `analysis` aren't plurals
+    if word.ends_with("ss") || word.ends_with("us") || word.ends_with("is") {
+        return word.to_owned();
+    }
+    match word.strip_suffix('s') {
+        Some(stem) if stem.len() >= MIN_STEM && has_vowel(stem) => stem.to_owned(),
+        _ => word.to_owned(),
+    }
+}
+
+/// `metering` -> `meter`, `controlled` -> `control`
+fn strip_inflection(word: &str) -> String {
+    // `speed` and `feed` aren't past tenses
+    if word.ends_with("eed") {
+        return word.to_owned();
+    }
+
+    for suffix in ["ing", "ed"] {
+        let Some(stem) = word.strip_suffix(suffix) else {
+            continue;
+        };
+        if stem.len() < MIN_STEM || !has_vowel(stem) {
+            continue;
+        }
+
+        // controll(ed) -> control
+        let bytes = stem.as_bytes();
+        let last = bytes[bytes.len() - 1];
+        let doubled = last == bytes[bytes.len() - 2]
+            && !matches!(last, b'a' | b'e' | b'i' | b'o' | b'u' | b's' | b'z');
+        return if doubled {
+            stem[..stem.len() - 1].to_owned()
+        } else {
+            stem.to_owned()
+        };
+    }
+
+    word.to_owned()
+}
+
+/// `measure` -> `measur`, to match `measur(ed)`
+fn strip_final_e(word: &str) -> String {
+    match word.strip_suffix('e') {
+        Some(stem) if stem.len() >= MIN_STEM && has_vowel(stem) => stem.to_owned(),
+        _ => word.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stems_inflections() {
+        for (word, expected) in [
+            ("sensors", "sensor"),
+            ("batteries", "battery"),
+            ("classes", "class"),
+            ("switches", "switch"),
+            ("metering", "meter"),
+            ("meters", "meter"),
+            ("controlled", "control"),
+            ("controlling", "control"),
+            ("measured", "measur"),
+            ("measure", "measur"),
+        ] {
+            assert_eq!(stem(word), expected, "{word}");
+        }
+    }
+
+    #[test]
+    fn keeps_other_endings() {
+        for word in [
+            "meter",
+            "speed",
+            "power",
+            "controller",
+            "bus",
+            "status",
+            "string",
+        ] {
+            assert_eq!(stem(word), word);
+        }
+        assert_eq!(stem("speeds"), "speed");
+        assert_eq!(stem("controllers"), "controller");
+    }
+
+    #[test]
+    fn finds_resource_titles() {
+        let krate: FullCrate = serde_json::from_value(serde_json::json!({
+            "name": "ina219",
+            "version": "0.1.0",
+            "released_at": "2024-01-01T00:00:00Z",
+            "downloads": 0,
+            "this_version_downloads": 0,
+            "description": "INA219 driver",
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "provenance": "Curated",
+            "names": ["INA219"],
+            "manufacturer": "TI",
+            "resources": [{
+                "title": "Measuring solar panel output",
+                "link": "https://example.com/solar",
+            }],
+            "license": "MIT",
+            "crate_size": null,
+            "dependencies": [],
+        }))
+        .unwrap();
+        let index = SearchIndex::from(&[krate][..]);
+
+        assert_eq!(index.search("solar panels"), vec![(0, 4)]);
+    }
+}
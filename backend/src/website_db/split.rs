@@ -0,0 +1,246 @@
+//! Crate database split into content-hashed files, so the website only loads what it shows
+//!
+//! `manifest.json` is the only file with a fixed name. It references a compact summary of all
+//! crates for the listing, one detail file per crate and one file per index. The names of those
+//! files contain a hash of their content, so they can be cached forever.
+//!
+//! The manifest of the previous run is kept as `manifest.previous.json`, together with its files,
+//! so clients that loaded it before an update can still fetch them.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path};
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::indexes::Indexes;
+use super::search::SearchIndex;
+use super::Interface;
+use crate::driver_db::boards::Board;
+use crate::driver_db::categories::{Category, CategoryNode};
+use crate::driver_db::manufacturers::ChipManufacturer;
+use crate::{FullCrate, Provenance};
+
+/// Number of hex digits of the content hash used in file names
+const HASH_LEN: usize = 16;
+
+const MANIFEST: &str = "manifest.json";
+/// Manifest of the previous run, whose files are kept
+const PREVIOUS_MANIFEST: &str = "manifest.previous.json";
+
+/// Entry point of the split crate database, tying the content-hashed files together
+///
+/// All paths are relative to the manifest.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SplitManifest {
+    /// List of [`CrateSummary`], in the order the indexes refer to
+    pub summary: String,
+    /// Tree of all categories, with the number of crates in each
+    pub categories: String,
+    /// All development boards, sorted by id
+    pub boards: String,
+    /// Full-text search index
+    pub search: String,
+    /// Index files, by name of the index
+    pub indexes: BTreeMap<String, String>,
+    /// Full crate descriptions, by crate name
+    pub crates: BTreeMap<String, String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SplitManifest {
+    /// Paths of all files this manifest references
+    pub fn files(&self) -> BTreeSet<&str> {
+        [&self.summary, &self.categories, &self.boards, &self.search]
+            .into_iter()
+            .chain(self.indexes.values())
+            .chain(self.crates.values())
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Read a manifest written by an earlier run, if there is one that can be read
+    fn read(path: &Path) -> Option<Self> {
+        let json = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+}
+
+/// Fields of a crate needed to list and sort it
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CrateSummary {
+    pub name: String,
+    pub version: semver::Version,
+    pub description: String,
+    pub downloads: u64,
+    pub this_version_downloads: u64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub license: String,
+    /// Names of the chips the driver supports
+    pub names: Vec<String>,
+    pub manufacturer: ChipManufacturer,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub categories: Vec<Category>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub interfaces: Vec<Interface>,
+    pub provenance: Provenance,
+    /// Declared or estimated minimum supported Rust version
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub msrv: Option<semver::Version>,
+}
+
+impl From<&FullCrate> for CrateSummary {
+    fn from(krate: &FullCrate) -> Self {
+        Self {
+            name: krate.name.clone(),
+            version: krate.version.clone(),
+            description: krate.description.clone(),
+            downloads: krate.downloads,
+            this_version_downloads: krate.this_version_downloads,
+            created_at: krate.created_at,
+            updated_at: krate.updated_at,
+            license: krate.license.clone(),
+            names: krate.chip_meta.names.clone(),
+            manufacturer: krate.chip_meta.manufacturer.clone(),
+            categories: krate.chip_meta.categories.clone(),
+            interfaces: krate.all_interfaces(),
+            provenance: krate.provenance,
+            msrv: krate.msrv.as_ref().map(|msrv| msrv.version.clone()),
+        }
+    }
+}
+
+/// Write the split crate database to `dir`, returning its manifest
+///
+/// The previous manifest is kept as `manifest.previous.json`. Files of the manifest before that
+/// are removed unless one of the newer manifests references them. Nothing else in `dir` is
+/// touched, and files of manifests that can't be read are left alone.
+pub fn write_split(
+    dir: &Path,
+    crates: &[FullCrate],
+    indexes: &Indexes,
+    search: &SearchIndex,
+    categories: &[CategoryNode],
+    boards: &[Board],
+    created_at: DateTime<Utc>,
+) -> anyhow::Result<SplitManifest> {
+    let previous = SplitManifest::read(&dir.join(MANIFEST));
+    let older = SplitManifest::read(&dir.join(PREVIOUS_MANIFEST));
+    let writer = HashedWriter::new(dir)?;
+
+    let summary: Vec<_> = crates.iter().map(CrateSummary::from).collect();
+    let manifest = SplitManifest {
+        summary: writer.write("summary", &summary)?,
+        categories: writer.write("categories", categories)?,
+        boards: writer.write("boards", boards)?,
+        search: writer.write("search", search)?,
+        indexes: split_indexes(indexes)?
+            .into_iter()
+            .map(|(name, index)| {
+                let path = writer.write(&format!("indexes/{name}"), &index)?;
+                Ok((name, path))
+            })
+            .collect::<anyhow::Result<_>>()?,
+        crates: crates
+            .iter()
+            .map(|krate| {
+                let path = writer.write(&format!("crates/{}", krate.name), krate)?;
+                Ok((krate.name.clone(), path))
+            })
+            .collect::<anyhow::Result<_>>()?,
+        created_at,
+    };
+
+    // Rewriting the same files doesn't start a new generation, that would drop the previous one
+    if let Some(previous) = previous.filter(|previous| previous.files() != manifest.files()) {
+        std::fs::rename(dir.join(MANIFEST), dir.join(PREVIOUS_MANIFEST))?;
+        if let Some(older) = older {
+            remove_unreferenced(dir, &older, &[&manifest, &previous])?;
+        }
+    }
+    std::fs::write(dir.join(MANIFEST), serde_json::to_string(&manifest)?)?;
+
+    Ok(manifest)
+}
+
+/// Each field of the indexes on its own
+fn split_indexes(indexes: &Indexes) -> anyhow::Result<serde_json::Map<String, serde_json::Value>> {
+    match serde_json::to_value(indexes)? {
+        serde_json::Value::Object(fields) => Ok(fields),
+        _ => anyhow::bail!("Indexes are not serialized as an object"),
+    }
+}
+
+/// Writes compact JSON files named after a hash of their content
+struct HashedWriter<'a> {
+    dir: &'a Path,
+}
+
+impl<'a> HashedWriter<'a> {
+    fn new(dir: &'a Path) -> anyhow::Result<Self> {
+        for subdir in ["", "crates", "indexes"] {
+            std::fs::create_dir_all(dir.join(subdir))?;
+        }
+
+        Ok(Self { dir })
+    }
+
+    /// Write `value` to `<stem>.<hash>.json`, returning the path relative to the output directory
+    fn write(&self, stem: &str, value: &(impl Serialize + ?Sized)) -> anyhow::Result<String> {
+        let json = serde_json::to_string(value)?;
+        let hash: String = Sha256::digest(json.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        let path = format!("{stem}.{}.json", &hash[..HASH_LEN]);
+        std::fs::write(self.dir.join(&path), json)?;
+        Ok(path)
+    }
+}
+
+/// Remove the files of `old` that none of `current` reference
+fn remove_unreferenced(
+    dir: &Path,
+    old: &SplitManifest,
+    current: &[&SplitManifest],
+) -> anyhow::Result<()> {
+    let referenced: BTreeSet<&str> = current
+        .iter()
+        .flat_map(|manifest| manifest.files())
+        .collect();
+
+    for path in old.files() {
+        // Only content-hashed files inside `dir`, whatever the manifest says
+        let relative = Path::new(path);
+        let inside = relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        let hashed = relative
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(is_hashed_name);
+
+        if inside && hashed && !referenced.contains(path) {
+            match std::fs::remove_file(dir.join(relative)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Does the file name look like `<stem>.<hash>.json`?
+fn is_hashed_name(name: &str) -> bool {
+    let Some(stem) = name.strip_suffix(".json") else {
+        return false;
+    };
+    let Some((_, hash)) = stem.rsplit_once('.') else {
+        return false;
+    };
+    hash.len() == HASH_LEN && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
@@ -1,169 +1,850 @@
 /* eslint-disable */
 /**
- * This file was automatically generated by json-schema-to-typescript.
- * DO NOT MODIFY IT BY HAND. Instead, modify the source JSONSchema file,
- * and run json-schema-to-typescript to regenerate this file.
+ * This file was automatically generated from the JSON schemas of the crate database.
+ * DO NOT MODIFY IT BY HAND. Instead, change the Rust types in `backend` and run
+ * `cargo run --bin read-driver-db -- typescript` to regenerate this file.
  */
 
-export type Category =
-  | "Analog"
-  | "Analog::ADC"
-  | "Analog::DAC"
-  | "Sensor"
-  | "Sensor::PowerMeter"
-  | "Sensor::Accelerometer"
-  | "Sensor::Gyroscope"
-  | "Sensor::Magnetometer"
-  | "IoExpander"
-  | "IoExpander::PWM"
-  | "Actor"
-  | "Actor::MotorController"
-  | "Display"
-  | "Display::OLED"
-  | "Timer"
-  | "Timer::RTC";
-export type ShortDependency = string;
-export type Connection = "StemmaQt" | "MikroBus";
-export type BoardManufacturer = "Adafruit" | "Sparkfun" | "Mikroe" | "BBC";
-export type Package = string;
-
+/**
+ * Crate database as written to `full-crate-db.json` by `read-driver-db`
+ */
 export interface FullCrateDb {
+  /**
+   * All development boards, sorted by id
+   */
+  boards: Board[];
+  /**
+   * Tree of all categories, with the number of crates in each
+   */
+  categories: CategoryNode[];
   crates: FullCrate[];
   created_at: string;
   indexes: Indexes;
+  /**
+   * Full-text search index over names, descriptions, part numbers and categories
+   */
+  search: SearchIndex;
 }
 
-export interface FullCrate {
+/**
+ * Entry point of the split crate database, tying the content-hashed files together
+ *
+ * All paths are relative to the manifest.
+ */
+export interface SplitManifest {
+  /**
+   * All development boards, sorted by id
+   */
+  boards: string;
+  /**
+   * Tree of all categories, with the number of crates in each
+   */
+  categories: string;
+  /**
+   * Full crate descriptions, by crate name
+   */
+  crates: {
+    [k: string]: string;
+  };
+  created_at: string;
+  /**
+   * Index files, by name of the index
+   */
+  indexes: {
+    [k: string]: string;
+  };
+  /**
+   * Full-text search index
+   */
+  search: string;
+  /**
+   * List of [`CrateSummary`], in the order the indexes refer to
+   */
+  summary: string;
+}
+
+/**
+ * Fields of a crate needed to list and sort it
+ */
+export interface CrateSummary {
+  categories?: Category[];
+  created_at: string;
   description: string;
+  downloads: number;
+  interfaces?: Interface[];
+  license: string;
+  manufacturer: ChipManufacturer;
+  /**
+   * Declared or estimated minimum supported Rust version
+   */
+  msrv?: SemVer | null;
+  name: string;
+  /**
+   * Names of the chips the driver supports
+   */
+  names: string[];
+  provenance: Provenance;
+  this_version_downloads: number;
+  updated_at: string;
+  version: SemVer;
+}
+
+/**
+ * Development or breakout board, as described in `boards/<id>.toml`
+ */
+export interface Board {
+  /**
+   * Chips on this board
+   */
+  chips?: BoardChip[];
+  /**
+   * Connectors for attaching the board without soldering, besides plain pin headers
+   */
+  connectors?: Connection[];
+  /**
+   * Id drivers use to reference this board, the name of its file
+   */
+  id: string;
+  /**
+   * Product page of the vendor
+   */
+  link: string;
+  name: string;
+  price_class?: PriceClass | null;
+  /**
+   * Product number of the vendor
+   */
+  sku?: string | null;
+  /**
+   * Solder jumpers for configuring the board
+   */
+  solder_jumpers?: SolderJumper[];
+  /**
+   * Company selling this board
+   */
+  vendor: string;
+}
+
+/**
+ * Node in the tree of categories
+ */
+export interface CategoryNode {
+  children?: CategoryNode[];
+  /**
+   * Number of crates in this category or any of its subcategories
+   */
+  count: number;
+  description: string;
+  id: Category;
+  /**
+   * Human-readable name
+   */
+  label: string;
+}
+
+export interface FullCrate {
   /**
    * Functionalities this driver provides
    */
   categories?: Category[];
-  crate_size?: number | null;
+  /**
+   * Connectors of the development boards
+   */
+  connectors?: Connection[];
+  crate_size: number | null;
   created_at: string;
   /**
    * Links to datasheets of chips that are supported by this driver
    */
   datasheets?: string[];
   dependencies: ShortDependency[];
-  dev_boards?: DevBoard[];
+  description: string;
+  /**
+   * Ids of the development boards housing a supported chip
+   */
+  dev_boards?: string[];
   documentation?: string | null;
   downloads: number;
+  /**
+   * Supply voltage, logic levels, current consumption and operating temperature
+   */
+  electrical?: Electrical | null;
   homepage?: string | null;
   interfaces?: Interfaces;
   /**
-   * Names of KiCAD symbold for chips this driver supports
+   * KiCad symbols checked against the KiCad libraries, if they were available
+   */
+  kicad?: KicadInfo | null;
+  /**
+   * KiCad symbols of chips this driver supports, as `Library:Symbol`, e.g. `Sensor_Motion:LIS3DH`
    */
   kicad_symbol?: string[];
   license: string;
   /**
    * Manufacturer that produces devices supported by this driver
    */
-  manufacturer:
-    | (
-        | "AnalogDevices"
-        | "TI"
-        | "NXP"
-        | "Toshiba"
-        | "SolomonSystech"
-        | "Unknown"
-      )
-    | "ST";
+  manufacturer: ChipManufacturer;
+  /**
+   * Specifications of the quantities measured by sensors this driver supports
+   */
+  measurements?: Measurement[];
+  /**
+   * Declared or estimated minimum supported Rust version
+   */
+  msrv?: Msrv | null;
   name: string;
   /**
    * Names of the chips this driver supports
    */
   names: string[];
+  /**
+   * Interfaces without a description in `interfaces`, like UART, or any interface of a legacy driver
+   */
+  other_interfaces?: Interface[];
   /**
    * Packages or footprints in which chips are available
    */
   packages?: Package[];
   /**
    * Part numbers of chips this driver supports
+   *
+   * Driver descriptions can give either just the part number or a table with details about the
+   * variant. Repeated part numbers are merged.
    */
-  part_numbers?: string[];
+  part_numbers?: PartNumber[];
+  provenance: Provenance;
+  /**
+   * When `version` was published
+   */
+  released_at: string;
   repository?: string | null;
   resources?: Resource[];
-  rust_version?: string | null;
+  /**
+   * Declared minimum supported Rust version
+   */
+  rust_version?: SemVer | null;
   this_version_downloads: number;
   updated_at: string;
-  version: string;
+  version: SemVer;
+}
 
-  [k: string]: unknown;
+export interface Indexes {
+  /**
+   * Crates supporting a chip on a development board, by board id
+   */
+  board: IndexForString;
+  category: IndexForCategory;
+  /**
+   * Crates with a development board that plugs into a connector, by the connector of the host
+   *
+   * Add-ons are listed under their host, e.g. FeatherWings under Feather. Cable connectors include
+   * compatible ones, e.g. boards with STEMMA QT are listed under Qwiic as well.
+   */
+  connector: IndexForConnection;
+  /**
+   * Normal dependencies that are always enabled, by semver-compatible range
+   */
+  dependencies: IndexForString;
+  /**
+   * Normal dependencies that are always enabled, by crate name
+   */
+  dependency_names: IndexForString;
+  /**
+   * Crates with at least one chip package that can be soldered by hand
+   */
+  hand_solderable: number[];
+  has_dev_board: number[];
+  /**
+   * Crates with KiCad symbols, only counting symbols that exist if the KiCad libraries were checked
+   */
+  has_kicad: number[];
+  interfaces: IndexForInterface;
+  license: IndexForString;
+  /**
+   * Quantities with a measurement specification
+   */
+  measured_quantity: IndexForMeasuredQuantity;
+  /**
+   * Normal dependencies that are enabled by a feature, by semver-compatible range
+   */
+  optional_dependencies: IndexForString;
+  package: IndexForPackageFamily;
+  /**
+   * How the packages connect to the board
+   */
+  package_style: IndexForPackageStyle;
+  /**
+   * Curated drivers and those only in the legacy list
+   */
+  provenance: IndexForProvenance;
+  /**
+   * Declared or estimated minimum supported Rust version
+   */
+  rust_version: IndexForSemVer;
+  /**
+   * Crates whose minimum supported Rust version was estimated
+   */
+  rust_version_estimated: number[];
+  /**
+   * Supply situations the chips can be used in, derived from their electrical characteristics
+   */
+  supply: IndexForSupplyClass;
+  /**
+   * Standard temperature grades covered by the operating temperature range
+   */
+  temperature_grade: IndexForTemperatureGrade;
 }
 
-export interface DevBoard {
-  connections: Connection[];
-  link: string;
-  manufacturer: BoardManufacturer;
+/**
+ * Inverted index from terms to the crates containing them
+ */
+export interface SearchIndex {
+  /**
+   * Weight of each crate supporting a chip, by chip name or part number
+   *
+   * Numbers are lowercase and without punctuation, e.g. `scd40dr2` for `SCD40-D-R2`.
+   */
+  part_numbers: {
+    [k: string]: {
+      [k: string]: number;
+    };
+  };
+  /**
+   * Weight of each crate containing a term, by stemmed term
+   */
+  terms: {
+    [k: string]: {
+      [k: string]: number;
+    };
+  };
+}
+
+export type Category =
+  | "Analog"
+  | "Analog::ADC"
+  | "Analog::DAC"
+  | "Analog::Multiplexer"
+  | "Analog::SignalGenerator"
+  | "Analog::DDS"
+  | "Sensor"
+  | "Sensor::PowerMeter"
+  | "Sensor::PowerMonitor"
+  | "Sensor::Accelerometer"
+  | "Sensor::Gyroscope"
+  | "Sensor::IMU"
+  | "Sensor::CO2"
+  | "Sensor::eCO2"
+  | "Sensor::TVOC"
+  | "Sensor::Temperature"
+  | "Sensor::Temperature::Thermocouple"
+  | "Sensor::Temperature::RTD"
+  | "Sensor::Temperature::Infrared"
+  | "Sensor::Humidity"
+  | "Sensor::Pressure"
+  | "Sensor::Barometer"
+  | "Sensor::Magnetometer"
+  | "Sensor::Light"
+  | "Sensor::AmbientLight"
+  | "Sensor::Light::Color"
+  | "Sensor::Light::UV"
+  | "Sensor::Distance"
+  | "Sensor::Proximity"
+  | "Sensor::Distance::ToF"
+  | "Sensor::Distance::Ultrasonic"
+  | "Sensor::Biometric"
+  | "Sensor::PulseOximeter"
+  | "Sensor::Angle"
+  | "IoExpander"
+  | "IoExpander::PWM"
+  | "IoExpander::GPIO"
+  | "IoExpander::BusMultiplexer"
+  | "Actor"
+  | "Actor::MotorController"
+  | "Actor::MotorController::Stepper"
+  | "Display"
+  | "Display::OLED"
+  | "Display::LCD"
+  | "Display::EPaper"
+  | "Display::EInk"
+  | "Display::LEDMatrix"
+  | "Display::LED-Matrix"
+  | "Display::Segment"
+  | "Timer"
+  | "Timer::RTC"
+  | "Timer::ClockGenerator"
+  | "Memory"
+  | "Memory::EEPROM"
+  | "Memory::Flash"
+  | "Memory::FRAM"
+  | "Memory::SRAM"
+  | "Memory::SDCard"
+  | "Wireless"
+  | "Wireless::LoRa"
+  | "Wireless::BLE"
+  | "Wireless::Bluetooth"
+  | "Wireless::SubGHz"
+  | "Wireless::2.4GHz"
+  | "Wireless::UWB"
+  | "Wireless::RFID"
+  | "Wireless::NFC"
+  | "Wireless::Cellular"
+  | "Wireless::WiFi"
+  | "Network"
+  | "Network::Ethernet"
+  | "Power"
+  | "Power::BatteryCharger"
+  | "Power::FuelGauge"
+  | "Power::USBPD"
+  | "Audio"
+  | "Audio::Amplifier"
+  | "Audio::Radio"
+  | "Input"
+  | "Input::Keypad"
+  | "Input::Touch"
+  | "Input::RotaryEncoder"
+  | "Input::Fingerprint";
+
+export type Interface =
+  | "I2C"
+  | "SPI"
+  | "UART"
+  | "GPIO"
+  | "OneWire"
+  | "ParallelPort"
+  | "Usb";
+
+export type ChipManufacturer =
+  | "AnalogDevices"
+  | "TI"
+  | "NXP"
+  | "Toshiba"
+  | "Sensirion"
+  | "SolomonSystech"
+  | "ScioSense"
+  | "Unknown"
+  | "ST";
+
+export type SemVer = string;
+
+/**
+ * Where a driver is listed
+ */
+export type Provenance = "Curated" | "Legacy";
+
+/**
+ * Chip on a board
+ */
+export interface BoardChip {
+  /**
+   * Crates in `driver-db` supporting this chip
+   */
+  drivers?: string[];
+  /**
+   * I2C address the chip has out of the box
+   */
+  i2c_address?: number | null;
+  /**
+   * Name of the chip, e.g. `LIS3DH`
+   */
   name: string;
+}
+
+/**
+ * Connector or form factor for attaching a board
+ *
+ * Standards with a main board and add-ons have a variant for each side, e.g. `Feather` and
+ * `FeatherWing`.
+ */
+export type Connection =
+  | "StemmaQt"
+  | "Qwiic"
+  | "Stemma"
+  | "Grove"
+  | "Gravity"
+  | "MikroBus"
+  | "MikroBusSocket"
+  | "Pmod"
+  | "PmodHost"
+  | "UEXT"
+  | "UEXTHost"
+  | "Feather"
+  | "FeatherWing"
+  | "RaspberryPi"
+  | "RaspberryPiHat"
+  | "Arduino"
+  | "ArduinoShield"
+  | "MicrobitEdge"
+  | "MicrobitAccessory";
+
+/**
+ * Rough price of a board
+ */
+export type PriceClass = "Budget" | "Low" | "Medium" | "High";
 
-  [k: string]: unknown;
+/**
+ * Solder jumper on a board
+ */
+export interface SolderJumper {
+  /**
+   * What closing or cutting the jumper does
+   */
+  description: string;
+  /**
+   * I2C address of the chip when the jumper is toggled
+   */
+  i2c_address?: number | null;
+  /**
+   * Label of the jumper on the board, e.g. `A0`
+   */
+  name: string;
+}
+
+/**
+ * Dependency of the newest version of a crate
+ *
+ * Dependencies for which no published version matches the requirement are kept,
+ * with the reason why they could not be resolved.
+ */
+export interface ShortDependency {
+  /**
+   * Are the default features of this dependency enabled?
+   */
+  default_features: boolean;
+  /**
+   * Whether this is a normal, build or dev dependency
+   */
+  kind: DependencyKind;
+  name: string;
+  /**
+   * Is this dependency only enabled by a feature?
+   */
+  optional: boolean;
+  /**
+   * Version requirement as published
+   */
+  req: string;
+  /**
+   * Target platform this dependency is restricted to (if any)
+   */
+  target?: string | null;
+  /**
+   * Why no version matching the requirement was found
+   */
+  unresolved?: UnresolvedReason | null;
+  /**
+   * Newest published version matching the requirement
+   */
+  version?: SemVer | null;
+}
+
+/**
+ * Electrical characteristics of a chip
+ */
+export interface Electrical {
+  /**
+   * Voltages the logic signals work with
+   */
+  logic_levels?: LogicLevel[];
+  /**
+   * Lowest and highest ambient temperature the chip can operate in
+   */
+  operating_temperature?: QuantityRange | null;
+  /**
+   * Current drawn in the lowest power mode that keeps the chip configured
+   */
+  sleep_current?: Quantity | null;
+  /**
+   * Lowest and highest supply voltage
+   */
+  supply_voltage?: QuantityRange | null;
+  /**
+   * Typical current drawn while active
+   */
+  typical_current?: Quantity | null;
 }
 
 export interface Interfaces {
   /**
    * Information about the I2C interface (if present)
    */
-  i2c?: I2C | null;
+  i2c?: I2c | null;
   /**
    * Information about the SPI interface (if present)
    */
   spi?: Spi | null;
 }
 
-export interface I2C {
+/**
+ * Result of checking the KiCad symbols of a driver against the libraries
+ */
+export interface KicadInfo {
   /**
-   * Addresses that can be used by this device
+   * Symbols matching the part numbers of a driver without any symbols
    */
-  addrs: number[];
+  suggestions?: string[];
   /**
-   * Does this device have an interrupt line?
+   * Symbols of the driver that exist in the libraries
    */
-  interrupt: boolean;
+  symbols?: KicadSymbol[];
+  /**
+   * Symbols of the driver that do not exist in the libraries
+   */
+  unknown?: string[];
 }
 
-export interface Spi {
+/**
+ * Specification of a quantity measured by a sensor
+ */
+export interface Measurement {
   /**
-   * Whether this device can be used on a shared bus or only on an exclusive device
+   * Maximum error of a measurement, either absolute or in %
    */
-  bus_type: "SpiBus" | "SpiDevice";
+  accuracy?: Quantity | null;
   /**
-   * Does this device have an interrupt line?
+   * Quantity that is measured
    */
-  interrupt: boolean;
+  quantity: MeasuredQuantity;
+  /**
+   * Lowest and highest value that can be measured
+   */
+  range?: QuantityRange | null;
+  /**
+   * Smallest change that can be measured
+   */
+  resolution?: Quantity | null;
+  /**
+   * Highest rate at which measurements can be taken
+   */
+  sample_rate?: Quantity | null;
+  /**
+   * Current drawn while measuring
+   */
+  supply_current?: Quantity | null;
+}
+
+/**
+ * Minimum supported Rust version of a crate
+ */
+export interface Msrv {
+  /**
+   * Where this version was derived from
+   */
+  source: MsrvSource;
+  version: SemVer;
+}
+
+/**
+ * Package name like the KiCad footprint names, e.g. `SOIC-8`, `SOT-23-5` or `QFN-32-1EP_5x5mm_P0.5mm`
+ */
+export type Package = string;
+
+/**
+ * Orderable variant of a chip
+ */
+export interface PartNumber {
+  /**
+   * Production status of this variant
+   */
+  lifecycle?: Lifecycle | null;
+  /**
+   * Part number as used by the manufacturer and distributors, e.g. `INA219AIDCNR`
+   */
+  number: string;
+  /**
+   * End of the part number that only selects packaging like reel size, e.g. `R` or ` T&R`
+   */
+  ordering_suffix?: string | null;
+  /**
+   * Package and pin count of this variant
+   */
+  package?: Package | null;
+  /**
+   * Temperature range this variant is specified for
+   */
+  temperature_grade?: TemperatureGrade | null;
 }
 
 export interface Resource {
-  title: string;
   link: string;
+  title: string;
 }
 
-export interface Indexes {
-  category: IndexFor_Category;
-  dependencies: IndexForString;
-  has_dev_board: number[];
-  has_kicad: number[];
-  interfaces: IndexFor_Interface;
-  license: IndexForString;
-  package: IndexFor_PackageType;
-  rust_version: IndexForString;
+/**
+ * Positions of the crates in the crate list, by key
+ */
+export interface IndexForString {
+  [k: string]: number[];
+}
 
-  [k: string]: unknown;
+/**
+ * Positions of the crates in the crate list, by key
+ */
+export interface IndexForCategory {
+  [k: string]: number[];
 }
 
-export interface IndexFor_Category {
+/**
+ * Positions of the crates in the crate list, by key
+ */
+export interface IndexForConnection {
   [k: string]: number[];
 }
 
-export interface IndexForString {
+/**
+ * Positions of the crates in the crate list, by key
+ */
+export interface IndexForInterface {
+  [k: string]: number[];
+}
+
+/**
+ * Positions of the crates in the crate list, by key
+ */
+export interface IndexForMeasuredQuantity {
+  [k: string]: number[];
+}
+
+/**
+ * Positions of the crates in the crate list, by key
+ */
+export interface IndexForPackageFamily {
+  [k: string]: number[];
+}
+
+/**
+ * Positions of the crates in the crate list, by key
+ */
+export interface IndexForPackageStyle {
+  [k: string]: number[];
+}
+
+/**
+ * Positions of the crates in the crate list, by key
+ */
+export interface IndexForProvenance {
+  [k: string]: number[];
+}
+
+/**
+ * Positions of the crates in the crate list, by key
+ */
+export interface IndexForSemVer {
   [k: string]: number[];
 }
 
-export interface IndexFor_Interface {
+/**
+ * Positions of the crates in the crate list, by key
+ */
+export interface IndexForSupplyClass {
   [k: string]: number[];
 }
 
-export interface IndexFor_PackageType {
+/**
+ * Positions of the crates in the crate list, by key
+ */
+export interface IndexForTemperatureGrade {
   [k: string]: number[];
 }
+
+export type DependencyKind = "Normal" | "Build" | "Dev";
+
+/**
+ * Reason why no version could be found for a dependency requirement
+ */
+export type UnresolvedReason = "UnknownCrate" | "NoMatchingVersion";
+
+/**
+ * Voltage of the logic signals of a chip
+ */
+export type LogicLevel = "1.8V" | "3.3V" | "5V" | "5V-tolerant";
+
+/**
+ * Range of a physical quantity, written as `[min, max]`
+ */
+export type QuantityRange = [Quantity, Quantity];
+
+/**
+ * Number followed by a unit symbol with optional SI prefix, e.g. `3.3 V` or `-40 °C`
+ */
+export type Quantity = string;
+
+export interface I2c {
+  /**
+   * Addresses that can be used by this device
+   */
+  addrs: number[];
+  /**
+   * Does this device have an interrupt line?
+   */
+  interrupt: boolean;
+}
+
+export interface Spi {
+  /**
+   * Whether this device can be used on a shared bus or only on an exclusive device
+   */
+  bus_type: SpiDeviceType;
+  /**
+   * Does this device have an interrupt line?
+   */
+  interrupt: boolean;
+}
+
+/**
+ * Symbol from the KiCad symbol libraries
+ */
+export interface KicadSymbol {
+  datasheet?: string | null;
+  description?: string | null;
+  /**
+   * Default footprint of the symbol
+   */
+  footprint?: string | null;
+  /**
+   * Is the default footprint part of the footprint libraries?
+   *
+   * Only known if the footprint libraries were read.
+   */
+  footprint_exists?: boolean | null;
+  /**
+   * Reference as `Library:Symbol`
+   */
+  name: string;
+}
+
+/**
+ * Physical quantity measured by a sensor
+ */
+export type MeasuredQuantity =
+  | "Temperature"
+  | "Humidity"
+  | "Pressure"
+  | "CO2"
+  | "eCO2"
+  | "TVOC"
+  | "Acceleration"
+  | "AngularRate"
+  | "MagneticField"
+  | "Illuminance"
+  | "Distance"
+  | "Current"
+  | "Voltage"
+  | "Power";
+
+export type MsrvSource = "Declared" | "Edition" | "Dependencies";
+
+/**
+ * Production status of a part
+ */
+export type Lifecycle =
+  | "Active"
+  | "NotRecommended"
+  | "LastTimeBuy"
+  | "Obsolete";
+
+/**
+ * Standard operating temperature ranges
+ */
+export type TemperatureGrade = "Commercial" | "Industrial" | "Automotive";
+
+export type SpiDeviceType = "SpiBus" | "SpiDevice";
@@ -0,0 +1,173 @@
+// Querying the search index precomputed by the backend,
+// mirrors backend/src/website_db/search.rs
+
+import type { SearchIndex } from "./crate-db";
+
+const MIN_PREFIX_LEN = 3;
+const MIN_PART_NUMBER_LEN = 4;
+const STOP_WORDS = [
+  "a",
+  "an",
+  "and",
+  "for",
+  "in",
+  "of",
+  "on",
+  "or",
+  "the",
+  "to",
+  "with",
+];
+const VOWELS = ["a", "e", "i", "o", "u", "y"];
+
+export interface SearchResult {
+  index: number;
+  score: number;
+}
+
+const MIN_STEM = 3;
+
+function hasVowel(stem: string): boolean {
+  return VOWELS.some((vowel) => stem.includes(vowel));
+}
+
+function stripPlural(word: string): string {
+  if (word.endsWith("ies") && word.length - 3 >= MIN_STEM - 1) {
+    return word.slice(0, -3) + "y";
+  }
+  if (word.endsWith("sses")) {
+    return word.slice(0, -2);
+  }
+  // Words like `bus`, `status` and `analysis` aren't plurals
+  if (word.endsWith("ss") || word.endsWith("us") || word.endsWith("is")) {
+    return word;
+  }
+  const stem = word.slice(0, -1);
+  if (word.endsWith("s") && stem.length >= MIN_STEM && hasVowel(stem)) {
+    return stem;
+  }
+  return word;
+}
+
+function stripInflection(word: string): string {
+  // `speed` and `feed` aren't past tenses
+  if (word.endsWith("eed")) {
+    return word;
+  }
+
+  for (const suffix of ["ing", "ed"]) {
+    if (!word.endsWith(suffix)) {
+      continue;
+    }
+    const stem = word.slice(0, -suffix.length);
+    if (stem.length < MIN_STEM || !hasVowel(stem)) {
+      continue;
+    }
+
+    // controll(ed) -> control
+    const last = stem[stem.length - 1];
+    const doubled =
+      last == stem[stem.length - 2] &&
+      !["a", "e", "i", "o", "u", "s", "z"].includes(last);
+    return doubled ? stem.slice(0, -1) : stem;
+  }
+
+  return word;
+}
+
+function stripFinalE(word: string): string {
+  const stem = word.slice(0, -1);
+  if (word.endsWith("e") && stem.length >= MIN_STEM && hasVowel(stem)) {
+    return stem;
+  }
+  return word;
+}
+
+export function stem(word: string): string {
+  if (/[0-9]/.test(word) || !/^[\x00-\x7f]*$/.test(word)) {
+    return word;
+  }
+
+  return stripFinalE(stripInflection(stripPlural(word)));
+}
+
+export function tokenize(text: string): string[] {
+  return text
+    .split(/[^\p{L}\p{N}]/u)
+    .filter((word) => word.length > 0)
+    .map((word) => word.toLowerCase())
+    .filter((word) => !STOP_WORDS.includes(word))
+    .map(stem);
+}
+
+export function normalizePartNumber(number: string): string {
+  return number.replace(/[^A-Za-z0-9]/g, "").toLowerCase();
+}
+
+function matchPartNumber(
+  index: SearchIndex,
+  query: string,
+): Map<number, number> {
+  const matches = new Map<number, number>();
+  if (query.length < MIN_PREFIX_LEN) {
+    return matches;
+  }
+
+  for (const [number, crates] of Object.entries(index.part_numbers)) {
+    const longer = number.startsWith(query);
+    const shorter =
+      number.length >= MIN_PART_NUMBER_LEN &&
+      number.length < query.length &&
+      query.startsWith(number);
+    if (!longer && !shorter) {
+      continue;
+    }
+
+    for (const [krate, weight] of Object.entries(crates)) {
+      const i = Number(krate);
+      matches.set(i, Math.max(matches.get(i) ?? 0, weight));
+    }
+  }
+  return matches;
+}
+
+// Crates matching the query, most relevant first
+export function search(index: SearchIndex, query: string): SearchResult[] {
+  const scores = new Map<number, number>();
+  const add = (krate: string | number, weight: number) => {
+    const i = Number(krate);
+    scores.set(i, (scores.get(i) ?? 0) + weight);
+  };
+
+  for (const token of tokenize(query)) {
+    for (const [krate, weight] of Object.entries(index.terms[token] ?? {})) {
+      add(krate, weight * 2);
+    }
+
+    if (token.length >= MIN_PREFIX_LEN) {
+      for (const [term, crates] of Object.entries(index.terms)) {
+        if (term != token && term.startsWith(token)) {
+          for (const [krate, weight] of Object.entries(crates)) {
+            add(krate, weight);
+          }
+        }
+      }
+    }
+  }
+
+  const candidates = new Set(
+    query
+      .split(/\s+/)
+      .map(normalizePartNumber)
+      .concat([normalizePartNumber(query)]),
+  );
+  for (const candidate of candidates) {
+    for (const [krate, weight] of matchPartNumber(index, candidate)) {
+      add(krate, weight * 2);
+    }
+  }
+
+  return [...scores.entries()]
+    .map(([index, score]) => ({ index, score }))
+    .sort((a, b) => b.score - a.score || a.index - b.index);
+}